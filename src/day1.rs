@@ -1,16 +1,24 @@
-use std::collections::HashMap;
-use std::fs::File;
+use std::error::Error;
+use std::fs::{self, File};
 use std::io::{BufRead, BufReader};
+use aho_corasick::AhoCorasick;
 
 // Part 1
 
-pub fn run_day_1_part_1() -> () {
+pub fn run_day_1_part_1(strict: bool) -> Result<(), Box<dyn Error>> {
     let file = File::open("inputs/day1.txt").expect("Failed to open file");
     let reader = BufReader::new(file);
     let lines: Vec<String> = reader.lines().map(|l| l.expect("Failed to read line")).collect();
+    if strict {
+        let calibrations = calibrate_lines(&lines, &DigitScanner::new(&Vocabulary { words: vec![] }));
+        print_calibration_report(&calibrations);
+        println!("Part 1: This sum of the calibration values: {}", strict_sum(&calibrations)?);
+        return Ok(())
+    }
     let calibration_values: Vec<u32> = lines.iter().map(|l| get_calibration_value_part1(l.to_string())).collect();
     let answer: u32 = calibration_values.iter().sum();
-    println!("Part 1: This sum of the calibration values: {}", answer)
+    println!("Part 1: This sum of the calibration values: {}", answer);
+    Ok(())
 }
 
 fn get_calibration_value_part1(line: String) -> u32 {
@@ -63,85 +71,277 @@ fn test_get_calibration_value_part1() {
 
 // Part 2
 
-pub fn run_day_1_part_2() -> () {
+// vocabulary is either the name of a built-in vocabulary or a path to a vocabulary file
+pub fn run_day_1_part_2(vocabulary: &str, strict: bool) -> Result<(), Box<dyn Error>> {
+    let scanner = DigitScanner::new(&Vocabulary::load(vocabulary)?);
     let file = File::open("inputs/day1.txt").expect("Failed to open file");
     let reader = BufReader::new(file);
     let lines: Vec<String> = reader.lines().map(|l| l.expect("Failed to read line")).collect();
-    let calibration_values: Vec<u32> = lines.iter().map(|l| get_calibration_value_part2(l.to_string())).collect();
+    if strict {
+        let calibrations = calibrate_lines(&lines, &scanner);
+        print_calibration_report(&calibrations);
+        println!("Part 2: This *correct* sum of the calibration values: {}", strict_sum(&calibrations)?);
+        return Ok(())
+    }
+    let calibration_values: Vec<u32> = lines.iter().map(|l| calibration_value(&scanner.tokens(l)).unwrap_or(0)).collect();
     let answer: u32 = calibration_values.iter().sum();
-    println!("Part 2: This *correct* sum of the calibration values: {}", answer)
-}
-
-fn get_calibration_value_part2(line: String) -> u32 {
-    let digits = HashMap::from([
-        ("one", '1'),
-        ("two", '2'),
-        ("three", '3'),
-        ("four", '4'),
-        ("five", '5'),
-        ("six", '6'),
-        ("seven", '7'),
-        ("eight", '8'),
-        ("nine", '9'),
-    ]);
-    // A mapping of the wordy digit strings to their char counterparts
-    // Similar to part 1, except we catch these words as digits also
-    // we concat the digit chars at the end just like part 1, then parse that as the u32, then
-    // return that value.
-    let mut chars_from_front: String = String::from("");
-    let mut chars_from_back: String = String::from("");
-    let mut first_char: Option<char> = None;
-    let mut last_char: Option<char> = None;
-    for char in line.chars() {
-        if first_char.is_some() {
-            break
-        }
-        if char.is_digit(10) {
-            first_char = Some(char)
+    println!("Part 2: This *correct* sum of the calibration values: {}", answer);
+    Ok(())
+}
+
+// The spelled out digits for a few languages. None of them include zero, to match the
+// puzzle, but a vocabulary file can.
+const BUILTIN_VOCABULARIES: [(&str, [&str; 9]); 5] = [
+    ("english", ["one", "two", "three", "four", "five", "six", "seven", "eight", "nine"]),
+    ("german", ["eins", "zwei", "drei", "vier", "fünf", "sechs", "sieben", "acht", "neun"]),
+    ("french", ["un", "deux", "trois", "quatre", "cinq", "six", "sept", "huit", "neuf"]),
+    ("spanish", ["uno", "dos", "tres", "cuatro", "cinco", "seis", "siete", "ocho", "nueve"]),
+    ("dutch", ["een", "twee", "drie", "vier", "vijf", "zes", "zeven", "acht", "negen"]),
+];
+
+// A mapping of the wordy digit strings to their digit counterparts
+#[derive(Debug, PartialEq, Eq)]
+struct Vocabulary {
+    words: Vec<(String, u32)>,
+}
+
+impl Vocabulary {
+    fn builtin(name: &str) -> Option<Self> {
+        let (_, words) = BUILTIN_VOCABULARIES.iter().find(|(language, _)| *language == name)?;
+        let words = words.iter().zip(1..).map(|(word, digit)| (String::from(*word), digit)).collect();
+        Some(Self { words })
+    }
+
+    // A built-in vocabulary by name, otherwise a vocabulary file
+    fn load(name_or_path: &str) -> Result<Self, String> {
+        if let Some(vocabulary) = Self::builtin(name_or_path) {
+            return Ok(vocabulary)
         }
-        chars_from_front += &char.to_string();
-        for (digit_word, digit_char) in &digits {
-            if chars_from_front.contains(digit_word) {
-                first_char = Some(*digit_char);
-                break
+        let contents = fs::read_to_string(name_or_path).map_err(|e| {
+            let builtins: Vec<&str> = BUILTIN_VOCABULARIES.iter().map(|(language, _)| *language).collect();
+            format!("{:?} isn't one of {} and couldn't be read as a file: {}", name_or_path, builtins.join(", "), e)
+        })?;
+        Self::parse(&contents).map_err(|e| format!("{}: {}", name_or_path, e))
+    }
+
+    // A vocabulary file has a word and its digit on each line, e.g. "zero 0"
+    fn parse(contents: &str) -> Result<Self, String> {
+        let mut words: Vec<(String, u32)> = vec![];
+        for (idx, line) in contents.lines().enumerate() {
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            match parts[..] {
+                [] => continue,
+                [word, digit] => {
+                    let digit = digit.parse::<u32>().ok().filter(|digit| *digit <= 9)
+                        .ok_or(format!("Line {}: expected a digit 0 through 9, found {:?}", idx + 1, digit))?;
+                    words.push((String::from(word), digit));
+                },
+                _ => return Err(format!("Line {}: expected a word and a digit, found {:?}", idx + 1, line)),
             }
         }
-    }
-    for char in line.chars().rev() {
-        if last_char.is_some() {
-            break
+        if words.is_empty() {
+            return Err(String::from("The vocabulary doesn't have any words"))
         }
-        if char.is_digit(10) {
-            last_char = Some(char);
-            break
+        Ok(Self { words })
+    }
+}
+
+#[test]
+fn test_vocabulary_builtin() {
+    let english = Vocabulary::builtin("english").unwrap();
+    assert_eq!(9, english.words.len());
+    assert_eq!((String::from("one"), 1), english.words[0]);
+    assert_eq!((String::from("nine"), 9), english.words[8]);
+    assert_eq!(None, Vocabulary::builtin("klingon"));
+
+    let scanner = DigitScanner::new(&Vocabulary::builtin("german").unwrap());
+    assert_eq!(Some(53), calibration_value(&scanner.tokens("xfünfzweidrei")));
+}
+
+#[test]
+fn test_vocabulary_parse() {
+    let vocabulary = Vocabulary::parse("zero 0\n\nuno 1\n").unwrap();
+    assert_eq!(
+        Vocabulary { words: vec![(String::from("zero"), 0), (String::from("uno"), 1)] },
+        vocabulary
+    );
+    let scanner = DigitScanner::new(&vocabulary);
+    assert_eq!(Some(10), calibration_value(&scanner.tokens("unozeroabc")));
+
+    assert_eq!(
+        Err(String::from("Line 2: expected a digit 0 through 9, found \"10\"")),
+        Vocabulary::parse("one 1\nten 10")
+    );
+    assert_eq!(
+        Err(String::from("Line 1: expected a word and a digit, found \"one\"")),
+        Vocabulary::parse("one")
+    );
+    assert_eq!(
+        Err(String::from("The vocabulary doesn't have any words")),
+        Vocabulary::parse("\n")
+    );
+}
+
+// A digit found in a line, either a plain digit or a spelled out one
+#[derive(Debug, PartialEq, Eq)]
+struct DigitToken {
+    position: usize,
+    text: String,
+    digit: u32,
+}
+
+// Finds every digit in a line in a single pass, using an Aho-Corasick automaton over the
+// plain digits and the digit words. Matches can overlap, so "eightwo" is an 8 and then a 2.
+struct DigitScanner {
+    automaton: AhoCorasick,
+    // The digit for each pattern, by pattern index
+    digits: Vec<u32>,
+}
+
+impl DigitScanner {
+    fn new(vocabulary: &Vocabulary) -> Self {
+        let mut patterns: Vec<String> = (0..=9).map(|digit: u32| digit.to_string()).collect();
+        let mut digits: Vec<u32> = (0..=9).collect();
+        for (word, digit) in &vocabulary.words {
+            patterns.push(word.clone());
+            digits.push(*digit);
         }
-        // We need to arrange the wordy digits in the correct direction
-        // e.g. "nine" and not "enin"
-        chars_from_back = [char.to_string(), chars_from_back].join("");
-        for (digit_word, digit_char) in &digits {
-            if chars_from_back.contains(digit_word) {
-                last_char = Some(*digit_char);
-                break
-            }
+        let automaton = AhoCorasick::new(&patterns).expect("Failed to build the digit scanner");
+        Self { automaton, digits }
+    }
+
+    // Every digit in the line, in the order they start
+    fn tokens(&self, line: &str) -> Vec<DigitToken> {
+        let mut tokens: Vec<DigitToken> = self.automaton.find_overlapping_iter(line).map(|m| DigitToken {
+            position: m.start(),
+            text: String::from(&line[m.start()..m.end()]),
+            digit: self.digits[m.pattern().as_usize()],
+        }).collect();
+        // Overlapping matches come out in the order they end
+        tokens.sort_by_key(|token| token.position);
+        tokens
+    }
+}
+
+// The first and last digits make up the calibration value, None if there aren't any digits
+fn calibration_value(tokens: &[DigitToken]) -> Option<u32> {
+    let first = tokens.first()?;
+    let last = tokens.last()?;
+    Some(first.digit * 10 + last.digit)
+}
+
+// Strict mode
+// Both parts score a line without any digits as 0, which hides corrupted lines in the sum.
+// In strict mode every line's digits are listed, and lines without any are an error.
+
+// What the scanner found on one line of the input
+#[derive(Debug, PartialEq, Eq)]
+struct LineCalibration {
+    line_number: usize,
+    tokens: Vec<DigitToken>,
+    value: Option<u32>,
+}
+
+fn calibrate_lines(lines: &[String], scanner: &DigitScanner) -> Vec<LineCalibration> {
+    lines.iter().enumerate().map(|(idx, line)| {
+        let tokens = scanner.tokens(line);
+        let value = calibration_value(&tokens);
+        LineCalibration { line_number: idx + 1, tokens, value }
+    }).collect()
+}
+
+fn print_calibration_report(calibrations: &[LineCalibration]) {
+    for calibration in calibrations {
+        let tokens: Vec<String> = calibration.tokens.iter()
+            .map(|token| format!("{:?}@{}", token.text, token.position))
+            .collect();
+        match calibration.value {
+            Some(value) => println!("Line {}: {} ==> {}", calibration.line_number, tokens.join(" "), value),
+            None => println!("Line {}: no digits found!", calibration.line_number),
         }
     }
-    let digit = [
-        first_char.unwrap_or('a').to_string(),
-        last_char.unwrap_or('a').to_string()
-    ].join(""); 
-    match digit.parse() {
-        Ok(num) => num,
-        Err(_) => 0
+    let contributing = calibrations.iter().filter(|calibration| calibration.value.is_some()).count();
+    println!("{} of {} lines contributed to the sum", contributing, calibrations.len());
+}
+
+fn strict_sum(calibrations: &[LineCalibration]) -> Result<u32, String> {
+    let without_digits: Vec<String> = calibrations.iter()
+        .filter(|calibration| calibration.value.is_none())
+        .map(|calibration| calibration.line_number.to_string())
+        .collect();
+    if !without_digits.is_empty() {
+        return Err(format!("Lines without any digits: {}", without_digits.join(", ")))
     }
+    Ok(calibrations.iter().filter_map(|calibration| calibration.value).sum())
+}
+
+#[test]
+fn test_strict_sum() {
+    let scanner = DigitScanner::new(&Vocabulary::builtin("english").unwrap());
+    let lines = vec![String::from("two1nine"), String::from("eightwothree")];
+    let calibrations = calibrate_lines(&lines, &scanner);
+    assert_eq!(
+        LineCalibration {
+            line_number: 1,
+            tokens: vec![
+                DigitToken { position: 0, text: String::from("two"), digit: 2 },
+                DigitToken { position: 3, text: String::from("1"), digit: 1 },
+                DigitToken { position: 4, text: String::from("nine"), digit: 9 },
+            ],
+            value: Some(29),
+        },
+        calibrations[0]
+    );
+    assert_eq!(Ok(112), strict_sum(&calibrations));
+
+    let lines = vec![
+        String::from("two1nine"),
+        String::from("abcdef"),
+        String::from("eightwothree"),
+        String::from("xyz"),
+    ];
+    assert_eq!(
+        Err(String::from("Lines without any digits: 2, 4")),
+        strict_sum(&calibrate_lines(&lines, &scanner))
+    );
+
+    // Part 1 only counts plain digits
+    let scanner = DigitScanner::new(&Vocabulary { words: vec![] });
+    let lines = vec![String::from("1abc2"), String::from("two")];
+    assert_eq!(
+        Err(String::from("Lines without any digits: 2")),
+        strict_sum(&calibrate_lines(&lines, &scanner))
+    );
+}
+
+#[test]
+fn test_digit_scanner_tokens() {
+    let scanner = DigitScanner::new(&Vocabulary::builtin("english").unwrap());
+    let tokens = scanner.tokens("eightwo7seven");
+    assert_eq!(
+        vec![
+            DigitToken { position: 0, text: String::from("eight"), digit: 8 },
+            DigitToken { position: 4, text: String::from("two"), digit: 2 },
+            DigitToken { position: 7, text: String::from("7"), digit: 7 },
+            DigitToken { position: 8, text: String::from("seven"), digit: 7 },
+        ],
+        tokens
+    );
+    assert_eq!(Some(87), calibration_value(&tokens));
+    assert_eq!(None, calibration_value(&scanner.tokens("abc")));
 }
 
 #[test]
-fn test_get_calibration_value_part2() {
-    assert_eq!(29, get_calibration_value_part2("two1nine".to_string()));
-    assert_eq!(83, get_calibration_value_part2("eightwothree".to_string()));
-    assert_eq!(13, get_calibration_value_part2("abcone2threexyz".to_string()));
-    assert_eq!(24, get_calibration_value_part2("xtwone3four".to_string()));
-    assert_eq!(42, get_calibration_value_part2("4nineeightseven2".to_string()));
-    assert_eq!(14, get_calibration_value_part2("zoneight234".to_string()));
-    assert_eq!(76, get_calibration_value_part2("7pqrstsixteen".to_string()));
+fn test_calibration_value_part2() {
+    // Similar to part 1, except the english digit words count as digits also
+    let scanner = DigitScanner::new(&Vocabulary::builtin("english").unwrap());
+    assert_eq!(Some(29), calibration_value(&scanner.tokens("two1nine")));
+    assert_eq!(Some(83), calibration_value(&scanner.tokens("eightwothree")));
+    assert_eq!(Some(13), calibration_value(&scanner.tokens("abcone2threexyz")));
+    assert_eq!(Some(24), calibration_value(&scanner.tokens("xtwone3four")));
+    assert_eq!(Some(42), calibration_value(&scanner.tokens("4nineeightseven2")));
+    assert_eq!(Some(14), calibration_value(&scanner.tokens("zoneight234")));
+    assert_eq!(Some(76), calibration_value(&scanner.tokens("7pqrstsixteen")));
+    assert_eq!(Some(82), calibration_value(&scanner.tokens("eightwo")));
+    assert_eq!(None, calibration_value(&scanner.tokens("abcdef")));
 }
\ No newline at end of file
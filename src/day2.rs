@@ -1,16 +1,105 @@
-use std::fs::File;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::fs::{self, File};
 use std::io::{BufRead, BufReader};
-use regex::Regex;
 
 // Part 1
 
-pub fn run_day_2_part_1() -> () {
+// bag is either a list like "red=12,green=13,blue=14" or the path of a file containing one
+// With explain, print why each excluded game isn't possible
+pub fn run_day_2_part_1(bag: &str, explain: bool) -> Result<(), Box<dyn Error>> {
+    let bag = Bag::load(bag)?;
     let file = File::open("inputs/day2.txt").expect("Failed to open file");
     let reader = BufReader::new(file);
     let lines: Vec<String> = reader.lines().map(|l| l.expect("Failed to read line")).collect();
-    let games: Vec<Game> = lines.iter().map(|l| game_from_string(l)).collect();
-    let answer = sum_of_possible_games(games);
-    println!("Part 1: This is the sum of the Game IDs of possible games: {}", answer)
+    let games = parse_games(&lines)?;
+    if explain {
+        for game in &games {
+            let violations = game.violations(&bag);
+            if violations.is_empty() {
+                continue
+            }
+            println!("Game {} is excluded, it needs at least {}", game.game_id, game.minimum_bag());
+            for violation in violations {
+                println!(
+                    "    round {} has {} {} cubes, the bag only has {}",
+                    violation.round, violation.count, violation.colour, violation.allowed
+                );
+            }
+        }
+    }
+    let answer = sum_of_possible_games(games, &bag);
+    println!("Part 1: This is the sum of the Game IDs of possible games: {}", answer);
+    Ok(())
+}
+
+// The cubes in the bag, by colour. Colours that aren't in the bag have no cubes.
+#[derive(Debug, PartialEq, Eq)]
+struct Bag {
+    cubes: BTreeMap<String, u32>,
+}
+
+// The bag from the puzzle: 12 red cubes, 13 green cubes, and 14 blue cubes
+impl Default for Bag {
+    fn default() -> Self {
+        Self::parse("red=12,green=13,blue=14").unwrap()
+    }
+}
+
+// Written the same way as the --bag flag, e.g. "blue=14,green=13,red=12"
+impl fmt::Display for Bag {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let pairs: Vec<String> = self.cubes.iter().map(|(colour, count)| format!("{}={}", colour, count)).collect();
+        write!(f, "{}", pairs.join(","))
+    }
+}
+
+impl Bag {
+    fn count(&self, colour: &str) -> u32 {
+        *self.cubes.get(colour).unwrap_or(&0)
+    }
+
+    // A bag given inline, otherwise a bag file
+    fn load(spec_or_path: &str) -> Result<Self, String> {
+        if spec_or_path.contains('=') {
+            return Self::parse(spec_or_path)
+        }
+        let contents = fs::read_to_string(spec_or_path)
+            .map_err(|e| format!("Couldn't read the bag file {:?}: {}", spec_or_path, e))?;
+        Self::parse(&contents).map_err(|e| format!("{}: {}", spec_or_path, e))
+    }
+
+    // "colour=count" pairs, separated by commas or new lines
+    fn parse(spec: &str) -> Result<Self, String> {
+        let mut cubes = BTreeMap::<String, u32>::new();
+        for pair in spec.split([',', '\n']).map(|pair| pair.trim()).filter(|pair| !pair.is_empty()) {
+            let Some((colour, count)) = pair.split_once('=') else {
+                return Err(format!("Expected something like \"red=12\", found {:?}", pair))
+            };
+            let count = count.trim().parse::<u32>()
+                .map_err(|_| format!("Expected a number of {} cubes, found {:?}", colour.trim(), count.trim()))?;
+            cubes.insert(String::from(colour.trim()), count);
+        }
+        Ok(Self { cubes })
+    }
+}
+
+#[test]
+fn test_bag_parse() {
+    let bag = Bag::parse("red=20, green = 5\nblue=9\n").unwrap();
+    assert_eq!(20, bag.count("red"));
+    assert_eq!(5, bag.count("green"));
+    assert_eq!(9, bag.count("blue"));
+    assert_eq!(0, bag.count("purple"));
+    assert_eq!(
+        Err(String::from("Expected something like \"red=12\", found \"red 12\"")),
+        Bag::parse("red 12")
+    );
+    assert_eq!(
+        Err(String::from("Expected a number of green cubes, found \"lots\"")),
+        Bag::parse("red=1,green=lots")
+    );
 }
 
 #[derive(Debug)]
@@ -21,23 +110,24 @@ struct Game {
 
 #[derive(Debug)]
 struct Round {
-    red: u32,
-    green: u32,
-    blue: u32,
+    cubes: BTreeMap<String, u32>,
 }
 
 // A round of the game, which is separated by a semicolon in each line of the input day2.txt
 // I got a little mixed up at first about the problem.
 // The cubes are put *back into the bag* after each round, so we simply check *the cube count of each round*.
 // If a round has more cubes than the allowed amounts, the game is impossible.
+// Rounds can have cubes of any colour, not just red, green and blue.
 impl Round {
     pub fn new() -> Self {
         return Self {
-            red: 0,
-            green: 0,
-            blue: 0
+            cubes: BTreeMap::new()
         }
     }
+
+    fn count(&self, colour: &str) -> u32 {
+        *self.cubes.get(colour).unwrap_or(&0)
+    }
 }
 
 impl Game {
@@ -48,71 +138,200 @@ impl Game {
         }
     }
 
-    fn possible(&self) -> bool {
-        for round in &self.rounds {
-            if round.red < 13 && round.green < 14 && round.blue < 15 {
-                continue
+    fn possible(&self, bag: &Bag) -> bool {
+        self.violations(bag).is_empty()
+    }
+
+    // Every round and colour with more cubes than the bag holds
+    fn violations(&self, bag: &Bag) -> Vec<Violation> {
+        let mut violations: Vec<Violation> = vec![];
+        for (idx, round) in self.rounds.iter().enumerate() {
+            for (colour, count) in &round.cubes {
+                if *count > bag.count(colour) {
+                    violations.push(Violation {
+                        round: idx + 1,
+                        colour: colour.clone(),
+                        count: *count,
+                        allowed: bag.count(colour),
+                    });
+                }
             }
-            return false
         }
-        return true
+        violations
     }
 
-    fn power(&self) -> u32 {
-        let mut red_min = 0;
-        let mut blue_min = 0;
-        let mut green_min = 0;
+    // The smallest bag this game could have been played with,
+    // the most cubes of each colour seen in any one round
+    fn minimum_bag(&self) -> Bag {
+        let mut cubes = BTreeMap::<String, u32>::new();
         for round in &self.rounds {
-            if round.red > red_min {
-                red_min = round.red
+            for (colour, count) in &round.cubes {
+                let colour_min = cubes.entry(colour.clone()).or_insert(0);
+                if *count > *colour_min {
+                    *colour_min = *count
+                }
+            }
+        }
+        Bag { cubes }
+    }
+
+    // The product of the fewest cubes of each colour that appears in the game.
+    // With enough colours that can be more than even a u64 holds.
+    fn power(&self) -> Result<u64, String> {
+        self.minimum_bag().cubes.values()
+            .try_fold(1_u64, |power, count| power.checked_mul(*count as u64))
+            .ok_or_else(|| format!("The power of game {} doesn't fit in a u64", self.game_id))
+    }
+}
+
+// A round that drew more cubes of a colour than the bag holds, rounds count from 1
+#[derive(Debug, PartialEq, Eq)]
+struct Violation {
+    round: usize,
+    colour: String,
+    count: u32,
+    allowed: u32,
+}
+
+// Where a game line stopped making sense, lines and columns count from 1
+#[derive(Debug, PartialEq, Eq)]
+struct GameParseError {
+    line: usize,
+    column: usize,
+    message: String,
+}
+
+impl fmt::Display for GameParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}, column {}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl Error for GameParseError {}
+
+fn parse_games(lines: &[String]) -> Result<Vec<Game>, GameParseError> {
+    lines.iter().enumerate().map(|(idx, line)| {
+        game_from_string(line).map_err(|e| GameParseError { line: idx + 1, ..e })
+    }).collect()
+}
+
+// Parses "Game <id>: <n> <colour>, <n> <colour>; <n> <colour> ..." and nothing else.
+// A trailing ";" is fine, but a colour can only show up once per round.
+// Errors are reported as if the line were line 1, parse_games fills in the real line.
+fn game_from_string(line: &str) -> Result<Game, GameParseError> {
+    let mut parser = GameParser { chars: line.chars().collect(), pos: 0 };
+    parser.skip_spaces();
+    parser.expect("Game")?;
+    if parser.skip_spaces() == 0 {
+        return Err(parser.error("expected a space after \"Game\""))
+    }
+    let mut game = Game::new(parser.number("a game id")?);
+    parser.skip_spaces();
+    parser.expect(":")?;
+    loop {
+        let mut round = Round::new();
+        loop {
+            parser.skip_spaces();
+            let count = parser.number("a number of cubes")?;
+            if parser.skip_spaces() == 0 {
+                return Err(parser.error("expected a space between the number and the colour"))
             }
-            if round.green > green_min {
-                green_min = round.green
+            let colour_column = parser.pos + 1;
+            let colour = parser.word("a colour")?;
+            if round.cubes.contains_key(&colour) {
+                return Err(GameParseError {
+                    line: 1,
+                    column: colour_column,
+                    message: format!("{} shows up more than once in this round", colour),
+                })
             }
-            if round.blue > blue_min {
-                blue_min = round.blue
+            round.cubes.insert(colour, count);
+            parser.skip_spaces();
+            if parser.peek() != Some(',') {
+                break
             }
+            parser.pos += 1;
         }
-        return red_min * blue_min * green_min
-    }
-}
-
-fn game_from_string(line: &str) -> Game {
-    let game_id_re = Regex::new(r"(\d+)").unwrap();
-    let game_id_match = game_id_re.find(line).unwrap();
-    let game_id_str = game_id_match.as_str();
-    let game_id = game_id_str.parse::<u32>().unwrap();
-    let round_strs: Vec<&str> = line.split(";").collect();
-    let count_re = Regex::new(r"(\d+)\s+(red|blue|green)").unwrap();
-    let mut game = Game::new(game_id);
-    for round_str in round_strs {
-        let mut round = Round::new(); 
-        for (_, [count, color]) in count_re.captures_iter(round_str).map(|c| c.extract()) {
-            let count_num = count.parse::<u32>().unwrap_or(0);
-            match color { 
-                "red" => {
-                    round.red += count_num;
-                },
-                "blue" => {
-                    round.blue += count_num;
-                },
-                "green" => {
-                    round.green += count_num;
-                }
-                _ => {
-                    panic!("lol what")
+        game.rounds.push(round);
+        match parser.peek() {
+            None => break,
+            Some(';') => {
+                parser.pos += 1;
+                parser.skip_spaces();
+                if parser.peek().is_none() {
+                    break
                 }
-            }
+            },
+            Some(_) => return Err(parser.error("expected \",\", \";\" or the end of the line")),
         }
-        game.rounds.push(round);
     }
-    return game
+    Ok(game)
 }
 
-fn sum_of_possible_games(games: Vec<Game>) -> u32 {
+struct GameParser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl GameParser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).cloned()
+    }
+
+    fn error(&self, message: &str) -> GameParseError {
+        GameParseError { line: 1, column: self.pos + 1, message: String::from(message) }
+    }
+
+    // Returns how many spaces were skipped
+    fn skip_spaces(&mut self) -> usize {
+        let start = self.pos;
+        while self.peek().is_some_and(|chr| chr.is_whitespace()) {
+            self.pos += 1;
+        }
+        self.pos - start
+    }
+
+    fn expect(&mut self, literal: &str) -> Result<(), GameParseError> {
+        let end = self.pos + literal.chars().count();
+        if end > self.chars.len() || !self.chars[self.pos..end].iter().copied().eq(literal.chars()) {
+            return Err(self.error(&format!("expected {:?}", literal)))
+        }
+        self.pos = end;
+        Ok(())
+    }
+
+    fn number(&mut self, what: &str) -> Result<u32, GameParseError> {
+        let start = self.pos;
+        while self.peek().is_some_and(|chr| chr.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        if start == self.pos {
+            return Err(self.error(&format!("expected {}", what)))
+        }
+        let digits: String = self.chars[start..self.pos].iter().collect();
+        digits.parse::<u32>().map_err(|_| GameParseError {
+            line: 1,
+            column: start + 1,
+            message: format!("{} is too big for {}", digits, what),
+        })
+    }
+
+    fn word(&mut self, what: &str) -> Result<String, GameParseError> {
+        let start = self.pos;
+        while self.peek().is_some_and(|chr| chr.is_alphabetic()) {
+            self.pos += 1;
+        }
+        if start == self.pos {
+            return Err(self.error(&format!("expected {}", what)))
+        }
+        Ok(self.chars[start..self.pos].iter().collect())
+    }
+}
+
+fn sum_of_possible_games(games: Vec<Game>, bag: &Bag) -> u32 {
     let mut sum: u32 = 0;
     for game in games {
-        if game.possible() {
+        if game.possible(bag) {
             sum += game.game_id;
         }
     }
@@ -121,37 +340,99 @@ fn sum_of_possible_games(games: Vec<Game>) -> u32 {
 
 #[test]
 fn test_game_from_string_counts() {
-    let game = game_from_string("Game 1: 3 blue; 4 red; 7 green, 2 red;");
-    assert_eq!(game.rounds[0].blue, 3);
-    assert_eq!(game.rounds[1].red, 4);
-    assert_eq!(game.rounds[2].green, 7);
+    let game = game_from_string("Game 1: 3 blue; 4 red; 7 green, 2 red;").unwrap();
+    assert_eq!(game.rounds[0].count("blue"), 3);
+    assert_eq!(game.rounds[1].count("red"), 4);
+    assert_eq!(game.rounds[2].count("green"), 7);
+}
+
+#[test]
+fn test_game_from_string_other_colours() {
+    let game = game_from_string("Game 7: 3 purple, 1 red; 2 orange, 5 purple").unwrap();
+    assert_eq!(game.rounds[0].count("purple"), 3);
+    assert_eq!(game.rounds[1].count("orange"), 2);
+    assert_eq!(game.rounds[1].count("red"), 0);
+    // The default bag doesn't have any purple cubes
+    assert!(!game.possible(&Bag::default()));
+    assert!(game.possible(&Bag::parse("red=1,orange=2,purple=5").unwrap()));
+    // 5 purple * 1 red * 2 orange
+    assert_eq!(Ok(10), game.power());
+}
+
+#[test]
+fn test_game_from_string_malformed() {
+    let error = |line: &str| game_from_string(line).unwrap_err().to_string();
+    assert_eq!("line 1, column 1: expected \"Game\"", error("Gaem 1: 3 blue"));
+    assert_eq!("line 1, column 6: expected a game id", error("Game x: 3 blue"));
+    assert_eq!("line 1, column 8: expected \":\"", error("Game 1 3 blue"));
+    assert_eq!("line 1, column 9: expected a number of cubes", error("Game 1: blue"));
+    assert_eq!("line 1, column 11: expected a colour", error("Game 1: 3 ,blue"));
+    assert_eq!("line 1, column 10: expected a space between the number and the colour", error("Game 1: 3blue"));
+    assert_eq!("line 1, column 18: red shows up more than once in this round", error("Game 1: 3 red, 2 red; 1 blue"));
+    assert_eq!("line 1, column 16: expected \",\", \";\" or the end of the line", error("Game 1: 3 blue | 4 red"));
+    assert_eq!("line 1, column 23: expected a number of cubes", error("Game 1: 3 blue; 4 red;; 1 green"));
+    assert_eq!("line 1, column 9: 99999999999 is too big for a number of cubes", error("Game 1: 99999999999 blue"));
+}
+
+#[test]
+fn test_parse_games() {
+    let lines = vec![
+        String::from("Game 1: 3 blue, 4 red"),
+        String::from("Game 2: 1 blue, 2 green"),
+        String::from("Game 3: 1 blue 2 green"),
+    ];
+    assert_eq!(
+        "line 3, column 16: expected \",\", \";\" or the end of the line",
+        parse_games(&lines).unwrap_err().to_string()
+    );
 }
 
 #[test]
 fn test_game_from_string_possible() {
-    let possible_game1 = game_from_string("Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green");
-    let possible_game2 = game_from_string("Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue");
-    let possible_game3 = game_from_string("Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green");
-    assert!(possible_game1.possible());
-    assert!(possible_game2.possible());
-    assert!(possible_game3.possible());
+    let possible_game1 = game_from_string("Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green").unwrap();
+    let possible_game2 = game_from_string("Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue").unwrap();
+    let possible_game3 = game_from_string("Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green").unwrap();
+    assert!(possible_game1.possible(&Bag::default()));
+    assert!(possible_game2.possible(&Bag::default()));
+    assert!(possible_game3.possible(&Bag::default()));
 }
 
 #[test]
 fn test_game_from_string_impossible() {
-    let impossible_game1 = game_from_string("Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red");
-    let impossible_game2 = game_from_string("Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red");
-    assert!(!impossible_game1.possible());
-    assert!(!impossible_game2.possible());
+    let impossible_game1 = game_from_string("Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red").unwrap();
+    let impossible_game2 = game_from_string("Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red").unwrap();
+    assert!(!impossible_game1.possible(&Bag::default()));
+    assert!(!impossible_game2.possible(&Bag::default()));
+}
+
+#[test]
+fn test_game_minimum_bag() {
+    let game = game_from_string("Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red").unwrap();
+    assert_eq!(Bag::parse("red=20,green=13,blue=6").unwrap(), game.minimum_bag());
+    assert_eq!("blue=6,green=13,red=20", game.minimum_bag().to_string());
+}
+
+#[test]
+fn test_game_violations() {
+    let game = game_from_string("Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red").unwrap();
+    assert_eq!(
+        vec![
+            Violation { round: 3, colour: String::from("blue"), count: 15, allowed: 14 },
+            Violation { round: 3, colour: String::from("red"), count: 14, allowed: 12 },
+        ],
+        game.violations(&Bag::default())
+    );
+    let game = game_from_string("Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green").unwrap();
+    assert_eq!(Vec::<Violation>::new(), game.violations(&Bag::default()));
 }
 
 #[test]
 fn test_sum_of_possible_games() {
-    let possible_game1 = game_from_string("Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green");
-    let possible_game2 = game_from_string("Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue");
-    let possible_game3 = game_from_string("Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green");
-    let impossible_game1 = game_from_string("Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red");
-    let impossible_game2 = game_from_string("Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red");
+    let possible_game1 = game_from_string("Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green").unwrap();
+    let possible_game2 = game_from_string("Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue").unwrap();
+    let possible_game3 = game_from_string("Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green").unwrap();
+    let impossible_game1 = game_from_string("Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red").unwrap();
+    let impossible_game2 = game_from_string("Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red").unwrap();
     let games = vec![
         possible_game1,
         possible_game2,
@@ -159,23 +440,46 @@ fn test_sum_of_possible_games() {
         impossible_game1,
         impossible_game2
     ];
-    let sum = sum_of_possible_games(games);
+    let sum = sum_of_possible_games(games, &Bag::default());
     assert_eq!(8, sum)
 }
 
+#[test]
+fn test_sum_of_possible_games_other_bag() {
+    let games = vec![
+        game_from_string("Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green").unwrap(),
+        game_from_string("Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue").unwrap(),
+        game_from_string("Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red").unwrap(),
+        game_from_string("Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red").unwrap(),
+        game_from_string("Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green").unwrap(),
+    ];
+    // Game 3 only needed more red cubes
+    let sum = sum_of_possible_games(games, &Bag::parse("red=20,green=13,blue=14").unwrap());
+    assert_eq!(11, sum)
+}
+
 // Part 2
 
-pub fn run_day_2_part_2() -> () {
+pub fn run_day_2_part_2() -> Result<(), Box<dyn Error>> {
     let file = File::open("inputs/day2.txt").expect("Failed to open file");
     let reader = BufReader::new(file);
     let lines: Vec<String> = reader.lines().map(|l| l.expect("Failed to read line")).collect();
-    let games: Vec<Game> = lines.iter().map(|l| game_from_string(l)).collect();
-    let answer: u32 = games.iter().map(|g| g.power()).sum();
-    println!("Part 2: This is the sum of \"powers\" of all games: {}", answer)
+    let games = parse_games(&lines)?;
+    let mut answer: u64 = 0;
+    for game in &games {
+        answer = answer.checked_add(game.power()?).ok_or("The sum of powers doesn't fit in a u64")?;
+    }
+    println!("Part 2: This is the sum of \"powers\" of all games: {}", answer);
+    Ok(())
 }
 
 #[test]
 fn test_game_power() {
-    let game = game_from_string("Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green");
-    assert_eq!(48, game.power());
+    let game = game_from_string("Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green").unwrap();
+    assert_eq!(Ok(48), game.power());
+    // 4 colours of 10000 cubes would overflow a u32, but not a u64
+    let game = game_from_string("Game 2: 10000 a, 10000 b, 10000 c, 10000 d").unwrap();
+    assert_eq!(Ok(10_000_000_000_000_000), game.power());
+    let game = game_from_string("Game 3: 10000 a, 10000 b, 10000 c, 10000 d, 10000 e, 10000 f").unwrap();
+    assert_eq!(Err(String::from("The power of game 3 doesn't fit in a u64")), game.power());
 }
\ No newline at end of file
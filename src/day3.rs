@@ -1,109 +1,310 @@
-use std::collections::HashSet;
+use std::collections::BTreeMap;
+use std::error::Error;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
+use crate::grid::{Grid, ALL_AROUND};
 
 // Part 1
 
-pub fn run_day_3_part_1() -> () {
+pub fn run_day_3_part_1() -> Result<(), Box<dyn Error>> {
     let file: File = File::open("inputs/day3.txt").expect("Failed to open file");
     let reader = BufReader::new(file);
     let lines: Vec<String> = reader.lines().map(|l| l.expect("Failed to read line")).collect();
-    let part_numbers: Vec<u32> = find_all_part_numbers(lines);
-    let answer: u32 = part_numbers.iter().sum();
-    println!("Part 1: The sum of part numbers: {}", answer)
+    let answer = sum_of_part_numbers(lines)?;
+    println!("Part 1: The sum of part numbers: {}", answer);
+    Ok(())
 }
 
-fn find_all_part_numbers(schematic_lines: Vec<String>) -> Vec<u32> { 
+fn sum_of_part_numbers(schematic_lines: Vec<String>) -> Result<u64, String> {
+    find_all_part_numbers(schematic_lines)?.iter()
+        .try_fold(0, |sum, part_number| add_to_sum(sum, *part_number as u64, "part numbers"))
+}
+
+// Both parts add up in a u64 whether or not the schematic is streamed, so both ways
+// give the same answer, or the same error when even a u64 isn't enough
+fn add_to_sum(sum: u64, value: u64, summed: &str) -> Result<u64, String> {
+    sum.checked_add(value).ok_or_else(|| format!("The sum of {} doesn't fit in a u64", summed))
+}
+
+fn find_all_part_numbers(schematic_lines: Vec<String>) -> Result<Vec<u32>, String> {
+    let schematic = Schematic::from_lines(&schematic_lines)?;
     let mut part_numbers: Vec<u32> = vec![];
-    for (i, line) in schematic_lines.iter().enumerate() { 
-        for (j, chr) in line.chars().enumerate() {
-            match chr {
-                '0'..='9' => continue,
-                '.' => continue,
-                _ => {
-                    // We've arrived at a symbol of some sort
-                    // Look for part numbers around this coordinate
-                    part_numbers = [part_numbers, find_part_numbers_around_coor(&schematic_lines, (i, j))].concat();
-                }
-            }
+    for symbol_idx in 0..schematic.symbols.len() {
+        // Every number around every symbol, so a number next to two symbols counts twice
+        for number_idx in &schematic.numbers_around[symbol_idx] {
+            part_numbers.push(schematic.numbers[*number_idx].value);
         }
     }
-    return part_numbers
+    Ok(part_numbers)
 }
 
-fn find_part_numbers_around_coor(schematic_lines: &Vec<String>, coor: (usize, usize)) -> Vec<u32> {
-    let mut part_numbers: Vec<u32> = vec![];
-    let mut part_number_coors = HashSet::new();
-    let moves: Vec<(i32, i32)> = vec![
-        (-1, -1),
-        (-1, 0),
-        (-1, 1),
-        (0, 1),
-        (1, 1),
-        (1, 0),
-        (1, -1),
-        (0, -1),
-    ];
-    let (i, j) = coor;
-    println!("Walking around {:?}", (i, j));
-    for m in &moves {
-        let row_idx = i as i32 + m.0;
-        let col_idx = j as i32 + m.1;
-        println!("    👀 {:?}", (row_idx, col_idx));
-        let row = schematic_lines.get(row_idx as usize);
-        if row.is_some() {
-            let row_string: &str = row.unwrap();
-            let col = row_string.chars().nth(col_idx as usize);
-            if col.is_some() {
-                let col_char: char = col.unwrap();
-                if col_char.is_digit(10) {
-                    println!("        Found digit at {:?}", (row_idx, col_idx));
-                    // We've found a digit around the coordinate.
-                    // Walk left to the start of the number, if we need to.
-                    // If that's the case, there will be a spot to our left, 
-                    // and it will be a number.
-                    let mut start_col_idx = col_idx;
-                    loop {
-                        if start_col_idx == 0 {
-                            break;
-                        }
-                        let left_char = row_string.chars().nth((start_col_idx - 1) as usize);
-                        if left_char.is_some() {
-                            if left_char.unwrap().is_digit(10) {
-                                start_col_idx = start_col_idx - 1;
-                                println!("            Walking back to {:?}", (row_idx, start_col_idx));
-                                continue;
-                            }
-                            break;
-                        }
+// A number in the schematic, covering columns start_col..end_col of its row
+#[derive(Debug, PartialEq, Eq)]
+struct NumberSpan {
+    row: usize,
+    start_col: usize,
+    end_col: usize,
+    value: u32,
+}
+
+// Anything that isn't a digit or a '.'
+#[derive(Debug, PartialEq, Eq)]
+struct Symbol {
+    row: usize,
+    col: usize,
+    chr: char,
+}
+
+// The schematic, read once into its numbers and symbols.
+// number_ids has the index of the number covering each cell, which is all it
+// takes to find the numbers around a symbol.
+struct Schematic {
+    grid: Grid<char>,
+    numbers: Vec<NumberSpan>,
+    symbols: Vec<Symbol>,
+    number_ids: Grid<Option<usize>>,
+    // For each symbol, the indices of the numbers touching it, in the order they're first seen
+    numbers_around: Vec<Vec<usize>>,
+}
+
+impl Schematic {
+    // Rows have to be the same length, but there can be any number of them
+    fn from_lines(lines: &[String]) -> Result<Self, String> {
+        let grid = Grid::from_lines(lines)?;
+        let mut numbers: Vec<NumberSpan> = vec![];
+        let mut symbols: Vec<Symbol> = vec![];
+        for (row, cells) in grid.rows().enumerate() {
+            let (row_numbers, row_symbols) = tokenize_row(row, cells)?;
+            numbers.extend(row_numbers);
+            symbols.extend(row_symbols);
+        }
+
+        let mut number_ids: Grid<Option<usize>> = Grid::new(grid.width(), grid.height(), vec![None; grid.width() * grid.height()]);
+        for (number_idx, number) in numbers.iter().enumerate() {
+            for col in number.start_col..number.end_col {
+                number_ids[(number.row, col)] = Some(number_idx);
+            }
+        }
+
+        let numbers_around = symbols.iter().map(|symbol| {
+            let mut around: Vec<usize> = vec![];
+            for pos in grid.neighbours8((symbol.row, symbol.col)) {
+                if let Some(number_idx) = number_ids[pos] {
+                    if !around.contains(&number_idx) {
+                        around.push(number_idx);
                     }
-                    println!("                Adding coor to set {:?}", (row_idx, start_col_idx));
-                    let number_coor = (row_idx as usize, start_col_idx as usize);
-                    part_number_coors.insert(number_coor);
                 }
             }
-        }
+            around
+        }).collect();
+
+        Ok(Self { grid, numbers, symbols, number_ids, numbers_around })
+    }
+}
+
+// Symbol analytics
+// $ cargo run -- --day 3 --part 1 --symbol-report
+
+pub fn run_day_3_symbol_report() -> Result<(), Box<dyn Error>> {
+    let file: File = File::open("inputs/day3.txt").expect("Failed to open file");
+    let reader = BufReader::new(file);
+    let lines: Vec<String> = reader.lines().map(|l| l.expect("Failed to read line")).collect();
+    let schematic = Schematic::from_lines(&lines)?;
+    println!("{:<8} {:>8} {:>10} {:>12}", "symbol", "count", "numbers", "sum");
+    for (chr, stats) in schematic.symbol_stats()? {
+        println!("{:<8} {:>8} {:>10} {:>12}", chr, stats.symbol_count, stats.number_count, stats.number_sum);
     }
-    for part_number_coor in &part_number_coors {
-        let mut part_number_str = String::from("");
-        let row_idx = part_number_coor.0;
-        let mut col_idx = part_number_coor.1;
-        loop {
-            if col_idx == schematic_lines.len() {
-                break
+    let shared = schematic.shared_numbers();
+    println!();
+    println!("{} numbers touch more than one symbol, and count more than once in part 1:", shared.len());
+    for (number_idx, symbol_idxs) in &shared {
+        let number = &schematic.numbers[*number_idx];
+        let symbols: Vec<String> = symbol_idxs.iter().map(|idx| {
+            let symbol = &schematic.symbols[*idx];
+            format!("{} at {:?}", symbol.chr, (symbol.row, symbol.col))
+        }).collect();
+        println!("    {} at {:?} touches {}", number.value, (number.row, number.start_col), symbols.join(", "));
+    }
+    println!("Sum of part numbers counting each number once: {}", schematic.part_number_sum()?);
+    Ok(())
+}
+
+// What's around all the symbols with the same character
+#[derive(Debug, Default, PartialEq, Eq)]
+struct SymbolStats {
+    symbol_count: usize,
+    number_count: usize,
+    number_sum: u64,
+}
+
+impl Schematic {
+    // For each number, the indices of the symbols touching it
+    fn symbols_around(&self) -> Vec<Vec<usize>> {
+        let mut symbols_around: Vec<Vec<usize>> = vec![vec![]; self.numbers.len()];
+        for (symbol_idx, numbers_around) in self.numbers_around.iter().enumerate() {
+            for number_idx in numbers_around {
+                symbols_around[*number_idx].push(symbol_idx);
             }
-            let row = schematic_lines.get(row_idx).unwrap();
-            let part_number_chr = row.chars().nth(col_idx).unwrap();
-            if part_number_chr.is_digit(10) {
-                part_number_str.push(part_number_chr);
-                col_idx += 1;
-                continue
+        }
+        symbols_around
+    }
+
+    // Numbers can each be close to u32::MAX, so they're summed as u64s, checked all the same
+    fn symbol_stats(&self) -> Result<BTreeMap<char, SymbolStats>, String> {
+        let mut stats = BTreeMap::<char, SymbolStats>::new();
+        for (symbol, numbers_around) in self.symbols.iter().zip(&self.numbers_around) {
+            let symbol_stats = stats.entry(symbol.chr).or_default();
+            symbol_stats.symbol_count += 1;
+            symbol_stats.number_count += numbers_around.len();
+            for number_idx in numbers_around {
+                symbol_stats.number_sum = symbol_stats.number_sum.checked_add(self.numbers[*number_idx].value as u64)
+                    .ok_or_else(|| format!("The sum of the numbers around {} symbols doesn't fit in a u64", symbol.chr))?;
             }
-            break
         }
-        part_numbers.push(part_number_str.parse::<u32>().unwrap());
+        Ok(stats)
+    }
+
+    // The sum of the numbers next to any symbol, counting each of them once
+    fn part_number_sum(&self) -> Result<u64, String> {
+        self.symbols_around().iter().zip(&self.numbers)
+            .filter(|(symbols, _)| !symbols.is_empty())
+            .try_fold(0_u64, |sum, (_, number)| sum.checked_add(number.value as u64))
+            .ok_or_else(|| String::from("The sum of part numbers doesn't fit in a u64"))
+    }
+
+    // Numbers next to more than one symbol, with the symbols they're next to
+    fn shared_numbers(&self) -> Vec<(usize, Vec<usize>)> {
+        self.symbols_around().into_iter().enumerate().filter(|(_, symbols)| symbols.len() > 1).collect()
     }
-    return part_numbers 
+}
+
+#[test]
+fn test_schematic_symbol_stats() {
+    let lines = vec![
+        String::from("467..114.."),
+        String::from("...*......"),
+        String::from("..35..633."),
+        String::from("......#..."),
+        String::from("617*......"),
+        String::from(".....+.58."),
+        String::from("..592....."),
+        String::from("......755."),
+        String::from("...$.*...."),
+        String::from(".664.598.."),
+    ];
+    let stats = Schematic::from_lines(&lines).unwrap().symbol_stats().unwrap();
+    assert_eq!(Some(&SymbolStats { symbol_count: 3, number_count: 5, number_sum: 467 + 35 + 617 + 755 + 598 }), stats.get(&'*'));
+    assert_eq!(Some(&SymbolStats { symbol_count: 1, number_count: 1, number_sum: 633 }), stats.get(&'#'));
+    assert_eq!(Some(&SymbolStats { symbol_count: 1, number_count: 1, number_sum: 592 }), stats.get(&'+'));
+    assert_eq!(Some(&SymbolStats { symbol_count: 1, number_count: 1, number_sum: 664 }), stats.get(&'$'));
+}
+
+#[test]
+fn test_schematic_symbol_stats_big_numbers() {
+    // Sums that would overflow a u32
+    let lines = vec![
+        String::from("4000000000.4000000000"),
+        String::from("..........*.........#"),
+    ];
+    let schematic = Schematic::from_lines(&lines).unwrap();
+    let stats = schematic.symbol_stats().unwrap();
+    assert_eq!(Some(&SymbolStats { symbol_count: 1, number_count: 2, number_sum: 8000000000 }), stats.get(&'*'));
+    assert_eq!(Some(&SymbolStats { symbol_count: 1, number_count: 1, number_sum: 4000000000 }), stats.get(&'#'));
+    // The number next to both symbols only counts once
+    assert_eq!(Ok(8000000000), schematic.part_number_sum());
+}
+
+#[test]
+fn test_schematic_shared_numbers() {
+    let lines = vec![
+        String::from("#....."),
+        String::from(".12*.."),
+        String::from("....5$"),
+    ];
+    let schematic = Schematic::from_lines(&lines).unwrap();
+    // 12 touches # and *, 5 touches * and $
+    assert_eq!(vec![(0, vec![0, 1]), (1, vec![1, 2])], schematic.shared_numbers());
+    // So part 1 counts them both twice
+    let mut part_numbers = find_all_part_numbers(lines).unwrap();
+    part_numbers.sort();
+    assert_eq!(vec![5, 5, 12, 12], part_numbers);
+}
+
+// Split one row of the schematic into its numbers and symbols, in a single pass.
+// Fails on a number too big for a u32, which generated schematics can have.
+fn tokenize_row(row: usize, cells: &[char]) -> Result<(Vec<NumberSpan>, Vec<Symbol>), String> {
+    let mut numbers: Vec<NumberSpan> = vec![];
+    let mut symbols: Vec<Symbol> = vec![];
+    let mut number: Option<NumberSpan> = None;
+    for (col, chr) in cells.iter().enumerate() {
+        if let Some(digit) = chr.to_digit(10) {
+            let span = number.get_or_insert(NumberSpan { row, start_col: col, end_col: col, value: 0 });
+            span.value = span.value.checked_mul(10).and_then(|value| value.checked_add(digit)).ok_or_else(|| {
+                format!("Line {}: The number starting at column {} doesn't fit in a u32", row + 1, span.start_col + 1)
+            })?;
+            span.end_col = col + 1;
+            continue
+        }
+        if let Some(span) = number.take() {
+            numbers.push(span);
+        }
+        if *chr != '.' {
+            symbols.push(Symbol { row, col, chr: *chr });
+        }
+    }
+    if let Some(span) = number.take() {
+        numbers.push(span);
+    }
+    Ok((numbers, symbols))
+}
+
+#[test]
+fn test_tokenize_row() {
+    let cells: Vec<char> = "617*...#.58".chars().collect();
+    let (numbers, symbols) = tokenize_row(4, &cells).unwrap();
+    assert_eq!(
+        vec![
+            NumberSpan { row: 4, start_col: 0, end_col: 3, value: 617 },
+            NumberSpan { row: 4, start_col: 9, end_col: 11, value: 58 },
+        ],
+        numbers
+    );
+    assert_eq!(
+        vec![
+            Symbol { row: 4, col: 3, chr: '*' },
+            Symbol { row: 4, col: 7, chr: '#' },
+        ],
+        symbols
+    );
+}
+
+#[test]
+fn test_tokenize_row_overflow() {
+    let cells: Vec<char> = "4294967295*".chars().collect();
+    assert_eq!(4294967295, tokenize_row(0, &cells).unwrap().0[0].value);
+    let cells: Vec<char> = "..12345678901*".chars().collect();
+    assert_eq!(
+        Err(String::from("Line 3: The number starting at column 3 doesn't fit in a u32")),
+        tokenize_row(2, &cells)
+    );
+    let lines = vec![String::from("......"), String::from("..*..."), String::from("9999999999")];
+    assert!(Schematic::from_lines(&lines).is_err());
+}
+
+#[test]
+fn test_schematic_numbers_around() {
+    let lines = vec![
+        String::from("467..114.."),
+        String::from("...*......"),
+        String::from("..35..633."),
+    ];
+    let schematic = Schematic::from_lines(&lines).unwrap();
+    assert_eq!(4, schematic.numbers.len());
+    assert_eq!(1, schematic.symbols.len());
+    let around: Vec<u32> = schematic.numbers_around[0].iter().map(|idx| schematic.numbers[*idx].value).collect();
+    assert_eq!(vec![467, 35], around);
+    assert_eq!(Some(3), schematic.number_ids[(2, 7)]);
+    assert_eq!(None, schematic.number_ids[(1, 3)]);
 }
 
 #[test]
@@ -120,48 +321,217 @@ fn test_find_all_part_numbers() {
         String::from("...$.*...."),
         String::from(".664.598.."),
     ];
-    let part_numbers = find_all_part_numbers(schematic);
+    let part_numbers = find_all_part_numbers(schematic).unwrap();
     let part_numbers_sum: u32 = part_numbers.iter().sum();
     assert_eq!(4361, part_numbers_sum);
 }
 
+// Numbers touching the edges of the schematic, which doesn't have to be square.
+// Neighbours outside the schematic are skipped rather than wrapping around.
+#[test]
+fn test_find_all_part_numbers_edges() {
+    // A number in the last column of a schematic that's wider than it is tall
+    let schematic = vec![
+        String::from("......99"),
+        String::from("......*."),
+    ];
+    assert_eq!(Ok(vec![99]), find_all_part_numbers(schematic));
+
+    // A number starting in column 0
+    let schematic = vec![
+        String::from("12#...."),
+        String::from("......."),
+    ];
+    assert_eq!(Ok(vec![12]), find_all_part_numbers(schematic));
+
+    // A number filling a whole row, in a schematic taller than it is wide
+    let schematic = vec![
+        String::from("..."),
+        String::from("123"),
+        String::from("..$"),
+        String::from("..."),
+        String::from("7.."),
+    ];
+    assert_eq!(Ok(vec![123]), find_all_part_numbers(schematic));
+
+    // Numbers at the end of one row and the start of the next don't run together
+    let schematic = vec![
+        String::from("..12"),
+        String::from("34*."),
+    ];
+    assert_eq!(Ok(vec![12, 34]), find_all_part_numbers(schematic));
+
+    // Symbols in the corners
+    let schematic = vec![
+        String::from("#1.2"),
+        String::from("3..4"),
+        String::from("5..6"),
+        String::from("%7.@"),
+    ];
+    let mut part_numbers = find_all_part_numbers(schematic).unwrap();
+    part_numbers.sort();
+    assert_eq!(vec![1, 3, 5, 6, 7], part_numbers);
+}
+
+#[test]
+fn test_find_all_part_numbers_same_value() {
+    // Two different numbers with the same value around one symbol both count
+    let schematic = vec![
+        String::from("5.5"),
+        String::from(".*."),
+    ];
+    assert_eq!(Ok(vec![5, 5]), find_all_part_numbers(schematic));
+}
+
+#[test]
+fn test_schematic_not_rectangular() {
+    let schematic = vec![
+        String::from("467..114.."),
+        String::from("...*..."),
+    ];
+    assert!(Schematic::from_lines(&schematic).is_err());
+    assert_eq!(
+        Err(String::from("Line 2 is 1 long, but the lines above it are 2 long")),
+        find_all_part_numbers(vec![String::from("1*"), String::from("1")])
+    );
+    assert!(find_all_gear_ratios(schematic, &GearRule::default()).is_err());
+}
+
+#[test]
+fn test_find_all_part_numbers_overflow() {
+    let schematic = vec![
+        String::from("12345678901*"),
+    ];
+    assert_eq!(
+        Err(String::from("Line 1: The number starting at column 1 doesn't fit in a u32")),
+        find_all_part_numbers(schematic.clone())
+    );
+    assert!(find_all_gear_ratios(schematic, &GearRule::default()).is_err());
+}
+
 // Part 2
 
-pub fn run_day_3_part_2() -> () {
+// gear_rule is written like "*:2:product", see GearRule
+pub fn run_day_3_part_2(gear_rule: &str) -> Result<(), Box<dyn Error>> {
+    let gear_rule = GearRule::parse(gear_rule)?;
     let file: File = File::open("inputs/day3.txt").expect("Failed to open file");
     let reader = BufReader::new(file);
     let lines: Vec<String> = reader.lines().map(|l| l.expect("Failed to read line")).collect();
-    let gear_ratios: Vec<u32> = find_all_gear_ratios(lines);
-    let answer: u32 = gear_ratios.iter().sum();
-    println!("Part 1: The sum of gear ratios: {}", answer)
-}
-
-fn find_all_gear_ratios(schematic_lines: Vec<String>) -> Vec<u32> { 
-    let mut gear_ratios: Vec<u32> = vec![];
-    for (i, line) in schematic_lines.iter().enumerate() { 
-        for (j, chr) in line.chars().enumerate() {
-            match chr {
-                '0'..='9' => continue,
-                '.' => continue,
-                '*' => {
-                    // We've arrived at a * symbol
-                    // It might be a gear, if we're next to exactly two part numbers
-                    let part_numbers = find_part_numbers_around_coor(&schematic_lines, (i, j));
-                    if part_numbers.len() == 2 {
-                        // There are exactly two part numbers!
-                        // The multiple of the part numbers is the gear ratio
-                        let part_no1 = part_numbers.get(0).unwrap();
-                        let part_no2 = part_numbers.get(1).unwrap();
-                        let gear_ratio = part_no1 * part_no2;
-                        // Add it to the vector of gear ratios
-                        gear_ratios.push(gear_ratio);
-                    }
-                },
-                _ => continue
-            }
+    let answer = sum_of_gear_ratios(lines, &gear_rule)?;
+    println!("Part 1: The sum of gear ratios: {}", answer);
+    Ok(())
+}
+
+fn sum_of_gear_ratios(schematic_lines: Vec<String>, gear_rule: &GearRule) -> Result<u64, String> {
+    find_all_gear_ratios(schematic_lines, gear_rule)?.iter()
+        .try_fold(0, |sum, gear_ratio| add_to_sum(sum, *gear_ratio, "gear ratios"))
+}
+
+fn find_all_gear_ratios(schematic_lines: Vec<String>, gear_rule: &GearRule) -> Result<Vec<u64>, String> {
+    let schematic = Schematic::from_lines(&schematic_lines)?;
+    let mut gear_ratios: Vec<u64> = vec![];
+    for symbol_idx in schematic.gears(gear_rule) {
+        // and with the puzzle's rule the multiple of the two part numbers is the gear ratio
+        gear_ratios.push(schematic.gear_ratio(symbol_idx, gear_rule)?);
+    }
+    Ok(gear_ratios)
+}
+
+impl Schematic {
+    // Indices of the symbols that are gears.
+    // With the puzzle's rule, a * symbol is a gear if it's next to exactly two part numbers
+    fn gears(&self, gear_rule: &GearRule) -> Vec<usize> {
+        self.symbols.iter().zip(&self.numbers_around).enumerate()
+            .filter(|(_, (symbol, numbers_around))| symbol.chr == gear_rule.symbol && numbers_around.len() == gear_rule.neighbours)
+            .map(|(symbol_idx, _)| symbol_idx)
+            .collect()
+    }
+
+    fn gear_ratio(&self, symbol_idx: usize, gear_rule: &GearRule) -> Result<u64, String> {
+        let values: Vec<u64> = self.numbers_around[symbol_idx].iter().map(|idx| self.numbers[*idx].value as u64).collect();
+        gear_rule.combine.apply(&values).map_err(|e| gear_error(&self.symbols[symbol_idx], e))
+    }
+}
+
+// Which symbols count as gears, and how to get a gear's ratio from the numbers around it.
+// Written as "<symbol>:<number of neighbours>:<product|sum|min|max>", the puzzle's rule is "*:2:product".
+#[derive(Debug, PartialEq, Eq)]
+struct GearRule {
+    symbol: char,
+    neighbours: usize,
+    combine: Combine,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Combine {
+    Product,
+    Sum,
+    Min,
+    Max,
+}
+
+impl Combine {
+    // Enough big numbers around a gear can multiply or add up past a u64
+    fn apply(&self, values: &[u64]) -> Result<u64, String> {
+        let overflow = |combined: &str| {
+            let values: Vec<String> = values.iter().map(|value| value.to_string()).collect();
+            format!("The {} of {} doesn't fit in a u64", combined, values.join(", "))
+        };
+        match self {
+            Combine::Product => values.iter().try_fold(1_u64, |product, value| product.checked_mul(*value)).ok_or_else(|| overflow("product")),
+            Combine::Sum => values.iter().try_fold(0_u64, |sum, value| sum.checked_add(*value)).ok_or_else(|| overflow("sum")),
+            Combine::Min => Ok(values.iter().cloned().min().unwrap_or(0)),
+            Combine::Max => Ok(values.iter().cloned().max().unwrap_or(0)),
+        }
+    }
+}
+
+// Says which gear an error came from, counting lines and columns from 1
+fn gear_error(symbol: &Symbol, error: String) -> String {
+    format!("Line {}, column {}: {}", symbol.row + 1, symbol.col + 1, error)
+}
+
+impl Default for GearRule {
+    fn default() -> Self {
+        Self { symbol: '*', neighbours: 2, combine: Combine::Product }
+    }
+}
+
+impl GearRule {
+    fn parse(spec: &str) -> Result<Self, String> {
+        let expected = || format!("Expected a gear rule like \"*:2:product\", found {:?}", spec);
+        let parts: Vec<&str> = spec.split(':').collect();
+        let [symbol, neighbours, combine] = parts[..] else {
+            return Err(expected())
+        };
+        let mut symbol_chars = symbol.chars();
+        let (Some(symbol), None) = (symbol_chars.next(), symbol_chars.next()) else {
+            return Err(expected())
+        };
+        if symbol.is_ascii_digit() || symbol == '.' {
+            return Err(format!("{:?} can't be a gear, it isn't a symbol", symbol))
         }
+        let neighbours = neighbours.parse::<usize>().map_err(|_| expected())?;
+        let combine = match combine {
+            "product" => Combine::Product,
+            "sum" => Combine::Sum,
+            "min" => Combine::Min,
+            "max" => Combine::Max,
+            _ => return Err(format!("Expected product, sum, min or max to combine gear numbers, found {:?}", combine)),
+        };
+        Ok(Self { symbol, neighbours, combine })
     }
-    return gear_ratios
+}
+
+#[test]
+fn test_gear_rule_parse() {
+    assert_eq!(Ok(GearRule::default()), GearRule::parse("*:2:product"));
+    assert_eq!(Ok(GearRule { symbol: '#', neighbours: 3, combine: Combine::Sum }), GearRule::parse("#:3:sum"));
+    assert!(GearRule::parse("*:2").is_err());
+    assert!(GearRule::parse("**:2:product").is_err());
+    assert!(GearRule::parse("5:2:product").is_err());
+    assert!(GearRule::parse("*:two:product").is_err());
+    assert!(GearRule::parse("*:2:average").is_err());
 }
 
 #[test]
@@ -178,7 +548,503 @@ fn test_find_all_gear_ratios() {
         String::from("...$.*...."),
         String::from(".664.598.."),
     ];
-    let gear_ratios = find_all_gear_ratios(schematic);
-    let gear_ratios_sum: u32 = gear_ratios.iter().sum();
+    let gear_ratios = find_all_gear_ratios(schematic, &GearRule::default()).unwrap();
+    let gear_ratios_sum: u64 = gear_ratios.iter().sum();
     assert_eq!(467835, gear_ratios_sum);
 }
+
+#[test]
+fn test_find_all_gear_ratios_other_rules() {
+    let schematic = vec![
+        String::from("467..114.."),
+        String::from("...*......"),
+        String::from("..35..633."),
+        String::from("......#..."),
+        String::from("617*......"),
+        String::from(".....+.58."),
+        String::from("..592....."),
+        String::from("......755."),
+        String::from("...$.*...."),
+        String::from(".664.598.."),
+    ];
+    // The * next to just 617 counts with one neighbour
+    assert_eq!(Ok(vec![617]), find_all_gear_ratios(schematic.clone(), &GearRule::parse("*:1:product").unwrap()));
+    assert_eq!(Ok(vec![502, 1353]), find_all_gear_ratios(schematic.clone(), &GearRule::parse("*:2:sum").unwrap()));
+    assert_eq!(Ok(vec![633]), find_all_gear_ratios(schematic.clone(), &GearRule::parse("#:1:max").unwrap()));
+    assert_eq!(Ok(Vec::<u64>::new()), find_all_gear_ratios(schematic, &GearRule::parse("$:2:min").unwrap()));
+}
+
+#[test]
+fn test_find_all_gear_ratios_overflow() {
+    // Four 10 digit numbers multiply past a u64, but add up fine
+    let schematic = vec![
+        String::from("4000000000.4000000000"),
+        String::from("..........*.........."),
+        String::from("4000000000.4000000000"),
+    ];
+    let gear_rule = GearRule::parse("*:4:product").unwrap();
+    let error = "Line 2, column 11: The product of 4000000000, 4000000000, 4000000000, 4000000000 doesn't fit in a u64";
+    assert_eq!(Err(String::from(error)), find_all_gear_ratios(schematic.clone(), &gear_rule));
+    assert_eq!(
+        error,
+        stream_gear_ratios(schematic.join("\n").as_bytes(), &gear_rule, |_| Ok(())).unwrap_err().to_string()
+    );
+    assert_eq!(Err(String::from(error)), Schematic::from_lines(&schematic).unwrap().render(&gear_rule, false));
+    assert_eq!(Ok(vec![16000000000]), find_all_gear_ratios(schematic, &GearRule::parse("*:4:sum").unwrap()));
+    assert_eq!(Err(String::from("The sum of 18446744073709551615, 1 doesn't fit in a u64")), Combine::Sum.apply(&[u64::MAX, 1]));
+}
+
+#[test]
+fn test_find_all_gear_ratios_edges() {
+    // Same value on both sides of the gear
+    let schematic = vec![
+        String::from("5.5"),
+        String::from(".*."),
+    ];
+    assert_eq!(Ok(vec![25]), find_all_gear_ratios(schematic, &GearRule::default()));
+
+    // Gear in the last column, numbers reaching the right edge
+    let schematic = vec![
+        String::from("....12"),
+        String::from(".....*"),
+        String::from("...300"),
+    ];
+    assert_eq!(Ok(vec![3600]), find_all_gear_ratios(schematic, &GearRule::default()));
+
+    // Gear in column 0 with three numbers around it isn't a gear
+    let schematic = vec![
+        String::from("1..."),
+        String::from("*2.."),
+        String::from("3..."),
+    ];
+    assert_eq!(Ok(Vec::<u64>::new()), find_all_gear_ratios(schematic, &GearRule::default()));
+}
+
+// Rendering
+// $ cargo run -- --day 3 --part 2 --render [--no-color]
+
+pub fn run_day_3_render(gear_rule: &str, color: bool) -> Result<(), Box<dyn Error>> {
+    let gear_rule = GearRule::parse(gear_rule)?;
+    let file: File = File::open("inputs/day3.txt").expect("Failed to open file");
+    let reader = BufReader::new(file);
+    let lines: Vec<String> = reader.lines().map(|l| l.expect("Failed to read line")).collect();
+    let schematic = Schematic::from_lines(&lines)?;
+    print!("{}", schematic.render(&gear_rule, color)?);
+    Ok(())
+}
+
+// How a number or symbol is drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Highlight {
+    Plain,
+    PartNumber,
+    NotPartNumber,
+    // A gear, or a number around one. The index picks the gear's colour, so a gear
+    // and its numbers look alike
+    Gear(usize),
+    GearNumber(usize),
+}
+
+const GEAR_COLORS: [u8; 4] = [33, 35, 36, 34];
+
+// Wraps text in ANSI escapes, or without colour, in brackets: [part numbers],
+// {gears and their numbers}, and nothing for the rest
+fn highlight(text: &str, highlight: Highlight, color: bool) -> String {
+    match (highlight, color) {
+        (Highlight::Plain, _) | (Highlight::NotPartNumber, false) => text.to_string(),
+        (Highlight::PartNumber, true) => format!("\x1b[32m{}\x1b[0m", text),
+        (Highlight::NotPartNumber, true) => format!("\x1b[2m{}\x1b[0m", text),
+        (Highlight::Gear(gear_idx), true) => format!("\x1b[1;{}m{}\x1b[0m", GEAR_COLORS[gear_idx % GEAR_COLORS.len()], text),
+        (Highlight::GearNumber(gear_idx), true) => format!("\x1b[{}m{}\x1b[0m", GEAR_COLORS[gear_idx % GEAR_COLORS.len()], text),
+        (Highlight::PartNumber, false) => format!("[{}]", text),
+        (Highlight::Gear(_) | Highlight::GearNumber(_), false) => format!("{{{}}}", text),
+    }
+}
+
+impl Schematic {
+    // The schematic with its part numbers and gears picked out, followed by a line
+    // per gear linking it to the numbers around it
+    fn render(&self, gear_rule: &GearRule, color: bool) -> Result<String, String> {
+        let gears = self.gears(gear_rule);
+        let symbols_around = self.symbols_around();
+        let mut number_highlights: Vec<Highlight> = symbols_around.iter()
+            .map(|symbols| if symbols.is_empty() { Highlight::NotPartNumber } else { Highlight::PartNumber })
+            .collect();
+        let mut symbol_highlights: BTreeMap<(usize, usize), Highlight> = self.symbols.iter()
+            .map(|symbol| ((symbol.row, symbol.col), Highlight::Plain))
+            .collect();
+        // A number next to two gears takes the colour of the first
+        for (gear_idx, symbol_idx) in gears.iter().enumerate().rev() {
+            let symbol = &self.symbols[*symbol_idx];
+            symbol_highlights.insert((symbol.row, symbol.col), Highlight::Gear(gear_idx));
+            for number_idx in &self.numbers_around[*symbol_idx] {
+                number_highlights[*number_idx] = Highlight::GearNumber(gear_idx);
+            }
+        }
+
+        let mut rendered = String::new();
+        for row in 0..self.grid.height() {
+            let mut col = 0;
+            while col < self.grid.width() {
+                if let Some(number_idx) = self.number_ids[(row, col)] {
+                    let number = &self.numbers[number_idx];
+                    rendered.push_str(&highlight(&self.number_text(number_idx), number_highlights[number_idx], color));
+                    col = number.end_col;
+                    continue
+                }
+                let chr = self.grid[(row, col)].to_string();
+                let chr_highlight = symbol_highlights.get(&(row, col)).cloned().unwrap_or(Highlight::Plain);
+                rendered.push_str(&highlight(&chr, chr_highlight, color));
+                col += 1;
+            }
+            rendered.push('\n');
+        }
+
+        if !gears.is_empty() {
+            rendered.push('\n');
+        }
+        for (gear_idx, symbol_idx) in gears.iter().enumerate() {
+            let symbol = &self.symbols[*symbol_idx];
+            let numbers: Vec<String> = self.numbers_around[*symbol_idx].iter()
+                .map(|number_idx| highlight(&self.number_text(*number_idx), Highlight::GearNumber(gear_idx), color))
+                .collect();
+            rendered.push_str(&format!(
+                "{} at {:?}: {}, ratio {}\n",
+                highlight(&symbol.chr.to_string(), Highlight::Gear(gear_idx), color),
+                (symbol.row, symbol.col),
+                numbers.join(" and "),
+                self.gear_ratio(*symbol_idx, gear_rule)?
+            ));
+        }
+        Ok(rendered)
+    }
+
+    // A number the way it's written in the schematic, leading zeros and all,
+    // so the rendered rows still line up with the input
+    fn number_text(&self, number_idx: usize) -> String {
+        let number = &self.numbers[number_idx];
+        self.grid.row(number.row)[number.start_col..number.end_col].iter().collect()
+    }
+}
+
+#[test]
+fn test_render_without_color() {
+    let lines = vec![
+        String::from("467..114.."),
+        String::from("...*......"),
+        String::from("..35..633."),
+        String::from("......#..."),
+        String::from("617*......"),
+    ];
+    let schematic = Schematic::from_lines(&lines).unwrap();
+    let expected = "\
+{467}..114..
+...{*}......
+..{35}..[633].
+......#...
+[617]*......
+
+{*} at (1, 3): {467} and {35}, ratio 16345
+";
+    assert_eq!(Ok(String::from(expected)), schematic.render(&GearRule::default(), false));
+    // When one number is enough the lone * becomes the gear, and the first * stops being one
+    let expected = "\
+[467]..114..
+...*......
+..[35]..[633].
+......#...
+{617}{*}......
+
+{*} at (4, 3): {617}, ratio 617
+";
+    assert_eq!(Ok(String::from(expected)), schematic.render(&GearRule::parse("*:1:product").unwrap(), false));
+}
+
+#[test]
+fn test_render_leading_zeros() {
+    let lines = vec![
+        String::from("007*."),
+        String::from("...05"),
+        String::from("0...."),
+    ];
+    let schematic = Schematic::from_lines(&lines).unwrap();
+    let expected = "\
+{007}{*}.
+...{05}
+0....
+
+{*} at (0, 3): {05} and {007}, ratio 35
+";
+    assert_eq!(Ok(String::from(expected)), schematic.render(&GearRule::default(), false));
+}
+
+#[test]
+fn test_render_with_color() {
+    let lines = vec![
+        String::from("467..114.."),
+        String::from("...*......"),
+        String::from("..35..633."),
+        String::from("......#..."),
+    ];
+    let rendered = Schematic::from_lines(&lines).unwrap().render(&GearRule::default(), true).unwrap();
+    let first_line = rendered.lines().next().unwrap();
+    assert_eq!("\x1b[33m467\x1b[0m..\x1b[2m114\x1b[0m..", first_line);
+    assert!(rendered.contains("\x1b[1;33m*\x1b[0m"));
+    assert!(rendered.contains("\x1b[32m633\x1b[0m"));
+    assert!(rendered.ends_with("\x1b[33m467\x1b[0m and \x1b[33m35\x1b[0m, ratio 16345\n"));
+}
+
+// Streaming
+// $ cargo run -- --day 3 --part 1 --stream --input huge_schematic.txt
+// Only the rows above and below the one being looked at are kept in memory, so
+// schematics with millions of rows don't have to fit in it.
+
+pub fn run_day_3_part_1_streaming(input: &str) -> Result<(), Box<dyn Error>> {
+    let reader = BufReader::new(File::open(input)?);
+    let answer = stream_sum_of_part_numbers(reader)?;
+    println!("Part 1: The sum of part numbers: {}", answer);
+    Ok(())
+}
+
+pub fn run_day_3_part_2_streaming(input: &str, gear_rule: &str) -> Result<(), Box<dyn Error>> {
+    let gear_rule = GearRule::parse(gear_rule)?;
+    let reader = BufReader::new(File::open(input)?);
+    let answer = stream_sum_of_gear_ratios(reader, &gear_rule)?;
+    println!("Part 2: The sum of gear ratios: {}", answer);
+    Ok(())
+}
+
+// The same sums as sum_of_part_numbers and sum_of_gear_ratios, added up as they stream past
+fn stream_sum_of_part_numbers<R: BufRead>(reader: R) -> Result<u64, Box<dyn Error>> {
+    let mut answer: u64 = 0;
+    stream_part_numbers(reader, |part_number| {
+        answer = add_to_sum(answer, part_number as u64, "part numbers")?;
+        Ok(())
+    })?;
+    Ok(answer)
+}
+
+fn stream_sum_of_gear_ratios<R: BufRead>(reader: R, gear_rule: &GearRule) -> Result<u64, Box<dyn Error>> {
+    let mut answer: u64 = 0;
+    stream_gear_ratios(reader, gear_rule, |gear_ratio| {
+        answer = add_to_sum(answer, gear_ratio, "gear ratios")?;
+        Ok(())
+    })?;
+    Ok(answer)
+}
+
+// Calls on_part_number with the same part numbers, in the same order, as find_all_part_numbers
+fn stream_part_numbers<R: BufRead>(reader: R, mut on_part_number: impl FnMut(u32) -> Result<(), String>) -> Result<(), Box<dyn Error>> {
+    stream_symbols(reader, |_, numbers_around| {
+        for value in numbers_around {
+            on_part_number(*value)?;
+        }
+        Ok(())
+    })
+}
+
+// Calls on_gear_ratio with the same gear ratios, in the same order, as find_all_gear_ratios
+fn stream_gear_ratios<R: BufRead>(reader: R, gear_rule: &GearRule, mut on_gear_ratio: impl FnMut(u64) -> Result<(), String>) -> Result<(), Box<dyn Error>> {
+    stream_symbols(reader, |symbol, numbers_around| {
+        if symbol.chr == gear_rule.symbol && numbers_around.len() == gear_rule.neighbours {
+            let values: Vec<u64> = numbers_around.iter().map(|value| *value as u64).collect();
+            on_gear_ratio(gear_rule.combine.apply(&values).map_err(|e| gear_error(symbol, e))?)?;
+        }
+        Ok(())
+    })
+}
+
+// One row of the window, with the index of the number covering each of its cells
+struct StreamedRow {
+    numbers: Vec<NumberSpan>,
+    symbols: Vec<Symbol>,
+    number_ids: Vec<Option<usize>>,
+}
+
+impl StreamedRow {
+    fn new(row: usize, cells: &[char]) -> Result<Self, String> {
+        let (numbers, symbols) = tokenize_row(row, cells)?;
+        let mut number_ids: Vec<Option<usize>> = vec![None; cells.len()];
+        for (number_idx, number) in numbers.iter().enumerate() {
+            number_ids[number.start_col..number.end_col].fill(Some(number_idx));
+        }
+        Ok(Self { numbers, symbols, number_ids })
+    }
+}
+
+// Calls on_symbol with every symbol and the values of the numbers around it, a row at a time,
+// once the row below it has been read. The numbers are in the order Schematic::numbers_around has them.
+fn stream_symbols<R: BufRead>(reader: R, mut on_symbol: impl FnMut(&Symbol, &[u32]) -> Result<(), String>) -> Result<(), Box<dyn Error>> {
+    // The previous, current and next rows
+    let mut window: [Option<StreamedRow>; 3] = [None, None, None];
+    let mut width: Option<usize> = None;
+    let mut lines = reader.lines().enumerate();
+    loop {
+        let next_row = match lines.next() {
+            Some((row, line)) => {
+                let cells: Vec<char> = line?.chars().collect();
+                match width {
+                    None => width = Some(cells.len()),
+                    Some(width) if width != cells.len() => {
+                        return Err(format!("Line {} is {} long, but the lines above it are {} long", row + 1, cells.len(), width).into())
+                    },
+                    Some(_) => (),
+                }
+                Some(StreamedRow::new(row, &cells)?)
+            },
+            None => None,
+        };
+        let done = next_row.is_none();
+        window.rotate_left(1);
+        window[2] = next_row;
+
+        if let Some(current) = &window[1] {
+            let width = width.unwrap_or(0);
+            for symbol in &current.symbols {
+                let mut seen: Vec<(isize, usize)> = vec![];
+                let mut numbers_around: Vec<u32> = vec![];
+                for (d_row, d_col) in ALL_AROUND {
+                    let Some(streamed_row) = &window[(1 + d_row) as usize] else {
+                        continue
+                    };
+                    let Some(col) = symbol.col.checked_add_signed(d_col).filter(|col| *col < width) else {
+                        continue
+                    };
+                    if let Some(number_idx) = streamed_row.number_ids[col] {
+                        if !seen.contains(&(d_row, number_idx)) {
+                            seen.push((d_row, number_idx));
+                            numbers_around.push(streamed_row.numbers[number_idx].value);
+                        }
+                    }
+                }
+                on_symbol(symbol, &numbers_around)?;
+            }
+        }
+        if done {
+            return Ok(())
+        }
+    }
+}
+
+#[test]
+fn test_streaming_matches_in_memory() {
+    use rand::rngs::StdRng;
+    use rand::seq::SliceRandom;
+    use rand::{Rng, SeedableRng};
+
+    let schematic = vec![
+        String::from("467..114.."),
+        String::from("...*......"),
+        String::from("..35..633."),
+        String::from("......#..."),
+        String::from("617*......"),
+        String::from(".....+.58."),
+        String::from("..592....."),
+        String::from("......755."),
+        String::from("...$.*...."),
+        String::from(".664.598.."),
+    ];
+    let input = schematic.join("\n");
+    let mut part_numbers: Vec<u32> = vec![];
+    stream_part_numbers(input.as_bytes(), |part_number| {
+        part_numbers.push(part_number);
+        Ok(())
+    }).unwrap();
+    assert_eq!(find_all_part_numbers(schematic.clone()), Ok(part_numbers));
+    let mut gear_ratios: Vec<u64> = vec![];
+    stream_gear_ratios(input.as_bytes(), &GearRule::default(), |gear_ratio| {
+        gear_ratios.push(gear_ratio);
+        Ok(())
+    }).unwrap();
+    assert_eq!(vec![16345, 451490], gear_ratios);
+
+    // Random schematics, with numbers against every edge and symbols sharing numbers
+    let mut rng = StdRng::seed_from_u64(2023);
+    for (height, width) in [(1, 1), (1, 20), (2, 7), (40, 1), (200, 60)] {
+        let schematic: Vec<String> = (0..height).map(|_| {
+            let mut row = String::new();
+            let mut digits_in_a_row = 0;
+            for _ in 0..width {
+                let chr = match rng.gen_range(0..10) {
+                    0..=3 => '.',
+                    // Numbers stop at 9 digits so they always fit in a u32
+                    4..=7 if digits_in_a_row < 9 => char::from(b'0' + rng.gen_range(0..10)),
+                    4..=7 => '.',
+                    8 => '*',
+                    _ => *['#', '$', '+', '/'].choose(&mut rng).unwrap(),
+                };
+                digits_in_a_row = if chr.is_ascii_digit() { digits_in_a_row + 1 } else { 0 };
+                row.push(chr);
+            }
+            row
+        }).collect();
+        // With and without a trailing newline
+        let input = schematic.join("\n") + if height % 2 == 0 { "\n" } else { "" };
+        let mut part_numbers: Vec<u32> = vec![];
+        stream_part_numbers(input.as_bytes(), |part_number| {
+            part_numbers.push(part_number);
+            Ok(())
+        }).unwrap();
+        assert_eq!(find_all_part_numbers(schematic.clone()), Ok(part_numbers));
+        for gear_rule in ["*:2:product", "#:1:sum", "*:3:max"] {
+            let gear_rule = GearRule::parse(gear_rule).unwrap();
+            let mut gear_ratios: Vec<u64> = vec![];
+            stream_gear_ratios(input.as_bytes(), &gear_rule, |gear_ratio| {
+                gear_ratios.push(gear_ratio);
+                Ok(())
+            }).unwrap();
+            assert_eq!(find_all_gear_ratios(schematic.clone(), &gear_rule), Ok(gear_ratios));
+        }
+    }
+}
+
+#[test]
+fn test_streaming_sums_match_in_memory() {
+    let schematic = vec![
+        String::from("467..114.."),
+        String::from("...*......"),
+        String::from("..35..633."),
+        String::from("......#..."),
+        String::from("617*......"),
+    ];
+    let input = schematic.join("\n");
+    assert_eq!(Ok(1752), sum_of_part_numbers(schematic.clone()));
+    assert_eq!(1752, stream_sum_of_part_numbers(input.as_bytes()).unwrap());
+    assert_eq!(Ok(16345), sum_of_gear_ratios(schematic, &GearRule::default()));
+    assert_eq!(16345, stream_sum_of_gear_ratios(input.as_bytes(), &GearRule::default()).unwrap());
+
+    // Each gear ratio fits in a u64, but the two of them add up past it, both ways
+    let schematic = vec![
+        String::from("4000000000*4000000000"),
+        String::from("....................."),
+        String::from("4000000000*4000000000"),
+    ];
+    let error = "The sum of gear ratios doesn't fit in a u64";
+    assert_eq!(Err(String::from(error)), sum_of_gear_ratios(schematic.clone(), &GearRule::default()));
+    assert_eq!(
+        error,
+        stream_sum_of_gear_ratios(schematic.join("\n").as_bytes(), &GearRule::default()).unwrap_err().to_string()
+    );
+    // Part numbers that overflow a u32 when they're added up are fine in a u64
+    assert_eq!(Ok(16000000000), sum_of_part_numbers(schematic.clone()));
+    assert_eq!(16000000000, stream_sum_of_part_numbers(schematic.join("\n").as_bytes()).unwrap());
+}
+
+#[test]
+fn test_streaming_edge_cases() {
+    let mut part_numbers: Vec<u32> = vec![];
+    stream_part_numbers("".as_bytes(), |part_number| {
+        part_numbers.push(part_number);
+        Ok(())
+    }).unwrap();
+    assert_eq!(Vec::<u32>::new(), part_numbers);
+    assert_eq!(
+        "Line 2 is 7 long, but the lines above it are 10 long",
+        stream_part_numbers("467..114..\n...*...".as_bytes(), |_| Ok(())).unwrap_err().to_string()
+    );
+    // A number too big to add up is an error rather than a panic, even in a stream
+    assert_eq!(
+        "Line 2: The number starting at column 1 doesn't fit in a u32",
+        stream_part_numbers("...........*\n12345678901.".as_bytes(), |_| Ok(())).unwrap_err().to_string()
+    );
+}
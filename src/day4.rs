@@ -1,44 +1,63 @@
-use std::collections::{HashSet, HashMap};
+use std::collections::{BTreeMap, HashSet};
+use std::error::Error;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
+use std::sync::OnceLock;
+use rand::rngs::StdRng;
+use rand::seq::index::sample;
+use rand::SeedableRng;
 use regex::Regex;
 
 // Part 1
 
-pub fn run_day_4_part_1() -> () {
-    let file: File = File::open("inputs/day4.txt").expect("Failed to open file");
+pub fn run_day_4_part_1(input: &str) -> Result<(), Box<dyn Error>> {
+    let file: File = File::open(input)?;
     let reader = BufReader::new(file);
     let lines: Vec<String> = reader.lines().map(|l| l.expect("Failed to read line")).collect();
-    let mut total_points = 0;
-    for line in lines {
-        total_points += count_points(line);
+    let mut total_points: u64 = 0;
+    for card in parse_cards(&lines)? {
+        total_points = total_points.checked_add(count_points(&card)?)
+            .ok_or("The sum of card points doesn't fit in a u64")?;
     }
-    println!("Part 1: The sum of card points in inputs/day4.txt ==> {}", total_points)
+    println!("Part 1: The sum of card points in {} ==> {}", input, total_points);
+    Ok(())
 }
 
-fn count_points(line: String) -> u32 { 
-    let card = Card::from_string(line);
+fn count_points(card: &Card) -> Result<u64, String> {
     let matching_nums_count = card.matching_nums.len() as u32;
     // If there are no winning nums, that counts for zero points
     if matching_nums_count < 1 {
-        return 0
+        return Ok(0)
     }
     // "The first match makes the card worth one point and each match after the first doubles the point value of that card.""
-    // Which works out to 2 ** (winning nums count - 1)
-    2_u32.pow(matching_nums_count - 1)
+    // Which works out to 2 ** (winning nums count - 1), as long as that fits
+    1_u64.checked_shl(matching_nums_count - 1).ok_or_else(|| {
+        format!("Card {} has {} matches, and 2^{} points doesn't fit in a u64", card.number, matching_nums_count, matching_nums_count - 1)
+    })
 }
 
 #[test]
 fn test_count_points() {
     let card1 = String::from("Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53");
-    assert_eq!(8, count_points(card1));
+    assert_eq!(Ok(8), count_points(&Card::from_string(card1).unwrap()));
     let card2 = String::from("Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19");
-    assert_eq!(2, count_points(card2));
+    assert_eq!(Ok(2), count_points(&Card::from_string(card2).unwrap()));
+}
+
+#[test]
+fn test_count_points_many_matches() {
+    // 40 matches would have overflowed a u32
+    let nums: Vec<String> = (1..=40).map(|num| num.to_string()).collect();
+    let card = format!("Card 7: {} | {}", nums.join(" "), nums.join(" "));
+    assert_eq!(Ok(1 << 39), count_points(&Card::from_string(card).unwrap()));
+    let nums: Vec<String> = (1..=65).map(|num| num.to_string()).collect();
+    let card = format!("Card 8: {} | {}", nums.join(" "), nums.join(" "));
+    assert_eq!(Err(String::from("Card 8 has 65 matches, and 2^64 points doesn't fit in a u64")), count_points(&Card::from_string(card).unwrap()));
 }
 
 // Part 2
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Eq)]
 struct Card {
     number: u32,
     winning_nums: Vec<u32>,
@@ -47,57 +66,157 @@ struct Card {
 }
 
 impl Card {
-    fn from_string(string: String) -> Self {
-        let re = Regex::new(r"(\d+)").unwrap(); 
-        let mut string_parts = string.split("|");
-        let winning_part = string_parts.next().unwrap();
-        let my_part = string_parts.next().unwrap();
-    
-        let mut winning_nums: Vec<u32> = re.find_iter(&winning_part).map(|m| m.as_str().parse::<u32>().unwrap()).collect();
-        let card_num = winning_nums.swap_remove(0); // Remove the first digit, which is the Card's number 
-        let scratched_nums: Vec<u32> = re.find_iter(&my_part).map(|m| m.as_str().parse::<u32>().unwrap()).collect();
-
-        let winning_nums_set: HashSet<u32> = winning_nums.clone().into_iter().collect();
+    // A line like "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53", with the numbers kept in order
+    fn from_string(string: String) -> Result<Self, String> {
+        static RE: OnceLock<Regex> = OnceLock::new();
+        let re = RE.get_or_init(|| Regex::new(r"^\s*Card\s+(\d+)\s*:([^|]*)\|([^|]*)$").unwrap());
+        let Some(captures) = re.captures(&string) else {
+            return Err(if string.matches('|').count() != 1 {
+                format!("Expected a single '|' between the winning and scratched numbers, found {:?}", string)
+            } else {
+                format!("Expected a card like \"Card 1: 41 48 | 83 86\", found {:?}", string)
+            })
+        };
+        let (_, [card_num, winning_part, my_part]) = captures.extract();
+        let card_num = card_num.parse::<u32>().map_err(|_| format!("Card number {} is too big", card_num))?;
+        let winning_nums = parse_nums(winning_part)?;
+        let scratched_nums = parse_nums(my_part)?;
+
+        let mut winning_nums_set: HashSet<u32> = HashSet::new();
+        for num in &winning_nums {
+            if !winning_nums_set.insert(*num) {
+                return Err(format!("Winning number {} is on card {} more than once", num, card_num))
+            }
+        }
         // Find the intersection of scratched_nums with winning_nums
-        let matching_nums = scratched_nums.iter().filter(|num| winning_nums_set.contains(&num)).cloned().collect(); 
-        return Self {
+        let matching_nums = scratched_nums.iter().filter(|num| winning_nums_set.contains(num)).cloned().collect();
+        Ok(Self {
             number: card_num,
             winning_nums,
             scratched_nums,
             matching_nums
+        })
+    }
+}
+
+fn parse_nums(part: &str) -> Result<Vec<u32>, String> {
+    part.split_whitespace()
+        .map(|num| num.parse::<u32>().map_err(|_| format!("Expected a number, found {:?}", num)))
+        .collect()
+}
+
+// Every card in the pile, which has to go Card 1, Card 2, Card 3 and so on since the
+// copies a card wins are the cards right after it. Blank lines are skipped.
+fn parse_cards(lines: &[String]) -> Result<Vec<Card>, String> {
+    let mut cards: Vec<Card> = vec![];
+    for (idx, line) in lines.iter().enumerate() {
+        if line.trim().is_empty() {
+            continue
+        }
+        let card = Card::from_string(line.to_string()).map_err(|e| format!("Line {}: {}", idx + 1, e))?;
+        let expected = cards.len() as u32 + 1;
+        if card.number != expected {
+            return Err(format!("Line {}: Expected card {}, found card {}", idx + 1, expected, card.number))
         }
+        cards.push(card);
     }
+    Ok(cards)
+}
+
+#[test]
+fn test_card_from_string() {
+    let card = Card::from_string(String::from("Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53")).unwrap();
+    assert_eq!(1, card.number);
+    assert_eq!(vec![41, 48, 83, 86, 17], card.winning_nums);
+    assert_eq!(vec![83, 86, 6, 31, 17, 9, 48, 53], card.scratched_nums);
+    assert_eq!(vec![83, 86, 17, 48], card.matching_nums);
+    let card = Card::from_string(String::from("Card  12:| 1 2")).unwrap();
+    assert_eq!(12, card.number);
+    assert_eq!(Vec::<u32>::new(), card.matching_nums);
+
+    assert_eq!(
+        Err(String::from("Expected a single '|' between the winning and scratched numbers, found \"Card 1: 41 48 83\"")),
+        Card::from_string(String::from("Card 1: 41 48 83"))
+    );
+    assert_eq!(
+        Err(String::from("Expected a single '|' between the winning and scratched numbers, found \"Card 1: 41 | 48 | 83\"")),
+        Card::from_string(String::from("Card 1: 41 | 48 | 83"))
+    );
+    assert_eq!(
+        Err(String::from("Expected a card like \"Card 1: 41 48 | 83 86\", found \"41 48 | 83\"")),
+        Card::from_string(String::from("41 48 | 83"))
+    );
+    assert_eq!(
+        Err(String::from("Expected a number, found \"4x\"")),
+        Card::from_string(String::from("Card 1: 41 4x | 83"))
+    );
+    assert_eq!(
+        Err(String::from("Winning number 41 is on card 3 more than once")),
+        Card::from_string(String::from("Card 3: 41 48 41 | 83"))
+    );
 }
 
-pub fn run_day_4_part_2() -> () {
-    let file: File = File::open("inputs/day4.txt").expect("Failed to open file");
+#[test]
+fn test_parse_cards() {
+    let lines = vec![
+        String::from("Card 1: 41 48 | 83 86"),
+        String::from("Card 2: 13 32 | 61 30"),
+        String::from(""),
+        String::from("Card 3: 1 21 | 69 82"),
+    ];
+    assert_eq!(3, parse_cards(&lines).unwrap().len());
+    let lines = vec![
+        String::from("Card 1: 41 48 | 83 86"),
+        String::from("Card 3: 1 21 | 69 82"),
+    ];
+    assert_eq!(Err(String::from("Line 2: Expected card 2, found card 3")), parse_cards(&lines).map(|cards| cards.len()));
+    let lines = vec![
+        String::from("Card 2: 13 32 | 61 30"),
+        String::from("Card 1: 41 48 | 83 86"),
+    ];
+    assert_eq!(Err(String::from("Line 1: Expected card 1, found card 2")), parse_cards(&lines).map(|cards| cards.len()));
+    let lines = vec![
+        String::from("Card 1: 41 48 | 83 86"),
+        String::from("Card 2: 13 32 61 30"),
+    ];
+    assert!(parse_cards(&lines).unwrap_err().starts_with("Line 2: Expected a single '|'"));
+}
+
+pub fn run_day_4_part_2(input: &str) -> Result<(), Box<dyn Error>> {
+    let file: File = File::open(input)?;
     let reader = BufReader::new(file);
-    let cards: Vec<Card> = reader.lines().map(|l| Card::from_string(l.expect("Failed to read line"))).collect();
-    let total_scratchcards = count_of_scratchcards_won(cards);
-    println!("Part 2: Total scratchcards won in inputs/day4.txt ==> {}", total_scratchcards);
-}
-
-fn count_of_scratchcards_won(stack: Vec<Card>) -> u32 {
-    let mut card_counts = HashMap::<u32, u32>::new();
-    let mut total_card_count = 0;
-    // Populate the card_hash with cards from the initial stack
-    for card in &stack {
-        card_counts.insert(card.number, 1);
-    }
-    for drawn_card in stack {
-        // draw the card n times, for 1 (the card in the original deck) + (every time we've added the card from previous matches) 
-        for (idx, _matched_num) in drawn_card.matching_nums.iter().enumerate() { 
-            let card_to_add_num = drawn_card.number + (idx as u32 + 1);
-            if let Some(card_count) = card_counts.get(&card_to_add_num) {
-                let drawn_times = card_counts.get(&drawn_card.number).unwrap();
-                card_counts.insert(card_to_add_num, card_count + drawn_times);
-            }
+    let lines: Vec<String> = reader.lines().map(|l| l.expect("Failed to read line")).collect();
+    let total_scratchcards = count_of_scratchcards_won(parse_cards(&lines)?)?;
+    println!("Part 2: Total scratchcards won in {} ==> {}", input, total_scratchcards);
+    Ok(())
+}
+
+fn count_of_scratchcards_won(stack: Vec<Card>) -> Result<u64, String> {
+    total_instances(&card_instances(&stack)?)
+}
+
+// How many of each card there are once all the copies are won, indexed like the stack.
+// parse_cards makes sure the cards are numbered up from 1 in order, so the cards a card
+// copies are the ones right after it.
+fn card_instances(stack: &[Card]) -> Result<Vec<u64>, String> {
+    let mut instances: Vec<u64> = vec![1; stack.len()];
+    for (idx, drawn_card) in stack.iter().enumerate() {
+        // Every instance of the drawn card wins one copy of each of the next cards,
+        // but never past the end of the table
+        let drawn_times = instances[idx];
+        let last_copy_idx = (idx + drawn_card.matching_nums.len()).min(stack.len() - 1);
+        for copy_idx in idx + 1..=last_copy_idx {
+            instances[copy_idx] = instances[copy_idx].checked_add(drawn_times).ok_or_else(|| {
+                format!("Card {} ends up with more than {} instances", stack[copy_idx].number, u64::MAX)
+            })?;
         }
     }
-    for (_card_num, count) in card_counts {
-        total_card_count += count;
-    }
-    return total_card_count 
+    Ok(instances)
+}
+
+fn total_instances(instances: &[u64]) -> Result<u64, String> {
+    instances.iter().try_fold(0_u64, |total, count| total.checked_add(*count))
+        .ok_or_else(|| format!("The total number of scratchcards is more than {}", u64::MAX))
 }
 
 #[test]
@@ -110,6 +229,330 @@ fn test_count_of_scratchcards_won() {
         String::from("Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36"),
         String::from("Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11"),
     ];
-    let cards = lines.iter().map(|l| Card::from_string(l.to_string())).collect();
-    assert_eq!(30, count_of_scratchcards_won(cards));
+    let cards = parse_cards(&lines).unwrap();
+    assert_eq!(Ok(30), count_of_scratchcards_won(cards));
+}
+
+#[test]
+fn test_count_of_scratchcards_won_overflow() {
+    // Two matches on every card makes the instances grow like the Fibonacci numbers,
+    // which passes u32 by card 46 and u64 by card 92
+    let cards = |count: u32| -> Vec<Card> {
+        (1..=count).map(|number| Card::from_string(format!("Card {}: 1 2 3 | 1 2 4", number)).unwrap()).collect()
+    };
+    assert_eq!(Ok(7778742001), count_of_scratchcards_won(cards(45)));
+    assert_eq!(Ok(12200160415121876646), count_of_scratchcards_won(cards(89)));
+    assert_eq!(Err(String::from("The total number of scratchcards is more than 18446744073709551615")), count_of_scratchcards_won(cards(91)));
+    assert_eq!(Err(String::from("Card 92 ends up with more than 18446744073709551615 instances")), count_of_scratchcards_won(cards(100)));
+}
+
+// Cascade
+// $ cargo run -- --day 4 --part 2 --cascade [table|tree|dot]
+
+pub fn run_day_4_cascade(format: &str, input: &str) -> Result<(), Box<dyn Error>> {
+    let reader = BufReader::new(File::open(input)?);
+    let mut lines: Vec<String> = vec![];
+    for line in reader.lines() {
+        lines.push(line?);
+    }
+    let cascade = cascade(&parse_cards(&lines)?)?;
+    match format {
+        "table" => print!("{}", cascade_table(&cascade)),
+        "tree" => print!("{}", cascade_tree(&cascade)?),
+        "dot" => print!("{}", cascade_to_dot(&cascade)),
+        _ => return Err(format!("Expected table, tree or dot for --cascade, found {:?}", format).into()),
+    }
+    Ok(())
+}
+
+// What happened to one card by the end of part 2
+#[derive(Debug, PartialEq, Eq)]
+struct CardCascade {
+    number: u32,
+    matches: usize,
+    // The cards each instance of this one wins a copy of
+    copies: Vec<u32>,
+    // The original card plus all the copies won of it
+    instances: u64,
+}
+
+fn cascade(stack: &[Card]) -> Result<Vec<CardCascade>, String> {
+    let instances = card_instances(stack)?;
+    // So the totals below can't overflow either
+    total_instances(&instances)?;
+    let cascade = stack.iter().zip(instances).enumerate().map(|(idx, (card, instances))| {
+        // Copies past the end of the table aren't won
+        let last_copy_idx = (idx + card.matching_nums.len()).min(stack.len() - 1);
+        let copies: Vec<u32> = stack[idx + 1..=last_copy_idx].iter().map(|copy| copy.number).collect();
+        CardCascade { number: card.number, matches: card.matching_nums.len(), copies, instances }
+    }).collect();
+    Ok(cascade)
+}
+
+fn cascade_table(cascade: &[CardCascade]) -> String {
+    let mut table = format!("{:<6} {:>7}  {:<24} {:>10}\n", "card", "matches", "copies", "instances");
+    for card in cascade {
+        let copies: Vec<String> = card.copies.iter().map(|number| number.to_string()).collect();
+        table.push_str(&format!("{:<6} {:>7}  {:<24} {:>10}\n", card.number, card.matches, copies.join(", "), card.instances));
+    }
+    let total: u64 = cascade.iter().map(|card| card.instances).sum();
+    table.push_str(&format!("Total scratchcards: {}\n", total));
+    table
+}
+
+// Every original card, with the copies it wins below it, and the copies they win below
+// them and so on, so there's a line for every scratchcard in the total
+const MAX_TREE_LINES: u64 = 10_000;
+
+fn cascade_tree(cascade: &[CardCascade]) -> Result<String, String> {
+    let total: u64 = cascade.iter().map(|card| card.instances).sum();
+    if total > MAX_TREE_LINES {
+        return Err(format!("The copy tree has {} scratchcards, too many to print, try --cascade dot", total))
+    }
+    let mut tree = String::new();
+    for (idx, card) in cascade.iter().enumerate() {
+        tree.push_str(&format!("Card {}\n", card.number));
+        push_copies(&mut tree, cascade, idx, "");
+    }
+    tree.push_str(&format!("{} scratchcards\n", total));
+    Ok(tree)
+}
+
+// The copies of a card are the cards right after it, like in card_instances
+fn push_copies(tree: &mut String, cascade: &[CardCascade], card_idx: usize, prefix: &str) {
+    let copies = &cascade[card_idx].copies;
+    for (offset, number) in copies.iter().enumerate() {
+        let last = offset == copies.len() - 1;
+        tree.push_str(&format!("{}{} Card {}\n", prefix, if last { "└──" } else { "├──" }, number));
+        let child_prefix = format!("{}{}", prefix, if last { "    " } else { "│   " });
+        push_copies(tree, cascade, card_idx + offset + 1, &child_prefix);
+    }
+}
+
+// An edge from each card to every card it copies, labelled with how many copies that adds
+fn cascade_to_dot(cascade: &[CardCascade]) -> String {
+    let mut dot = String::from("digraph cascade {\n");
+    for card in cascade {
+        dot.push_str(&format!("    \"{}\" [label=\"Card {}\\n{} matches\\n{} instances\"];\n", card.number, card.number, card.matches, card.instances));
+    }
+    for card in cascade {
+        for number in &card.copies {
+            dot.push_str(&format!("    \"{}\" -> \"{}\" [label=\"+{}\"];\n", card.number, number, card.instances));
+        }
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+#[test]
+fn test_cascade() {
+    let lines = [
+        String::from("Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53"),
+        String::from("Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19"),
+        String::from("Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1"),
+        String::from("Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83"),
+        String::from("Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36"),
+        String::from("Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11"),
+    ];
+    let cards = parse_cards(&lines).unwrap();
+    let cascade = cascade(&cards).unwrap();
+    assert_eq!(CardCascade { number: 1, matches: 4, copies: vec![2, 3, 4, 5], instances: 1 }, cascade[0]);
+    assert_eq!(CardCascade { number: 3, matches: 2, copies: vec![4, 5], instances: 4 }, cascade[2]);
+    assert_eq!(CardCascade { number: 5, matches: 0, copies: vec![], instances: 14 }, cascade[4]);
+    let instances: Vec<u64> = cascade.iter().map(|card| card.instances).collect();
+    assert_eq!(vec![1, 2, 4, 8, 14, 1], instances);
+    assert!(cascade_table(&cascade).ends_with("Total scratchcards: 30\n"));
+
+    let tree = cascade_tree(&cascade).unwrap();
+    // A line per scratchcard, plus the total
+    assert_eq!(31, tree.lines().count());
+    assert_eq!(14, tree.lines().filter(|line| line.ends_with("Card 5")).count());
+    let expected_start = "\
+Card 1
+├── Card 2
+│   ├── Card 3
+│   │   ├── Card 4
+│   │   │   └── Card 5
+│   │   └── Card 5
+│   └── Card 4
+│       └── Card 5
+├── Card 3
+";
+    assert!(tree.starts_with(expected_start));
+
+    let dot = cascade_to_dot(&cascade);
+    assert!(dot.contains("    \"3\" -> \"5\" [label=\"+4\"];\n"));
+    assert_eq!(9, dot.matches(" -> ").count());
+}
+
+#[test]
+fn test_cascade_copies_past_the_end() {
+    let cards = vec![
+        Card::from_string(String::from("Card 1: 1 2 | 1 2")).unwrap(),
+        Card::from_string(String::from("Card 2: 3 4 | 3 4")).unwrap(),
+    ];
+    let cascade = cascade(&cards).unwrap();
+    assert_eq!(vec![2], cascade[0].copies);
+    assert_eq!(Vec::<u32>::new(), cascade[1].copies);
+    assert_eq!(2, cascade[1].instances);
+}
+
+// Generating cards
+// $ cargo run -- --day 4 --part 1 --generate 1000 --seed 7 > cards.txt
+// $ cargo run -- --day 4 --part 1 --stats --input cards.txt
+
+pub fn run_day_4_generate(count: usize, winning: usize, scratched: usize, numbers: &str, seed: u64) -> Result<(), Box<dyn Error>> {
+    let generator = CardGenerator::new(winning, scratched, numbers)?;
+    for line in generator.generate(count, seed) {
+        println!("{}", line);
+    }
+    Ok(())
+}
+
+// Random cards shaped like the puzzle's, which has 10 winning and 25 scratched numbers
+// from 1-99. Neither side repeats a number, so every card is one parse_cards accepts.
+#[derive(Debug, PartialEq, Eq)]
+struct CardGenerator {
+    winning: usize,
+    scratched: usize,
+    low: u32,
+    high: u32,
+}
+
+impl CardGenerator {
+    // numbers is the range the numbers are drawn from, like "1-99"
+    fn new(winning: usize, scratched: usize, numbers: &str) -> Result<Self, String> {
+        let expected = || format!("Expected a range of numbers like \"1-99\", found {:?}", numbers);
+        let (low, high) = numbers.split_once('-').ok_or_else(expected)?;
+        let low = low.trim().parse::<u32>().map_err(|_| expected())?;
+        let high = high.trim().parse::<u32>().map_err(|_| expected())?;
+        if low > high {
+            return Err(expected())
+        }
+        let available = (high - low) as usize + 1;
+        if winning.max(scratched) > available {
+            return Err(format!("Can't pick {} different numbers from {}-{}", winning.max(scratched), low, high))
+        }
+        Ok(Self { winning, scratched, low, high })
+    }
+
+    // The same seed always gives the same cards
+    fn generate(&self, count: usize, seed: u64) -> Vec<String> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let available = (self.high - self.low) as usize + 1;
+        let card_width = count.to_string().len();
+        let num_width = self.high.to_string().len();
+        let mut draw = |amount: usize| -> String {
+            let nums: Vec<String> = sample(&mut rng, available, amount).iter()
+                .map(|offset| format!("{:>width$}", self.low + offset as u32, width = num_width))
+                .collect();
+            nums.join(" ")
+        };
+        (1..=count).map(|number| {
+            let winning = draw(self.winning);
+            let scratched = draw(self.scratched);
+            format!("Card {:>width$}: {} | {}", number, winning, scratched, width = card_width)
+        }).collect()
+    }
+}
+
+#[test]
+fn test_card_generator() {
+    let generator = CardGenerator::new(10, 25, "1-99").unwrap();
+    let lines = generator.generate(200, 2023);
+    assert_eq!(lines, generator.generate(200, 2023));
+    assert_ne!(lines, generator.generate(200, 2024));
+    assert!(lines[0].starts_with("Card   1: "));
+    let cards = parse_cards(&lines).unwrap();
+    assert_eq!(200, cards.len());
+    for card in &cards {
+        assert_eq!(10, card.winning_nums.len());
+        assert_eq!(25, card.scratched_nums.len());
+        assert_eq!(25, card.scratched_nums.iter().collect::<HashSet<_>>().len());
+        assert!(card.winning_nums.iter().chain(&card.scratched_nums).all(|num| (1..=99).contains(num)));
+    }
+
+    // Every number is in a range this narrow, so every card matches all its winning numbers
+    let generator = CardGenerator::new(3, 5, "10-14").unwrap();
+    let cards = parse_cards(&generator.generate(4, 1)).unwrap();
+    assert!(cards.iter().all(|card| card.matching_nums.len() == 3));
+
+    assert_eq!(Err(String::from("Can't pick 25 different numbers from 1-20")), CardGenerator::new(10, 25, "1-20"));
+    assert!(CardGenerator::new(10, 25, "99-1").is_err());
+    assert!(CardGenerator::new(10, 25, "1..99").is_err());
+}
+
+// Statistics
+// How the match counts of a pile of cards are spread, and what that's worth in both parts
+
+pub fn run_day_4_stats(input: &str) -> Result<(), Box<dyn Error>> {
+    let reader = BufReader::new(File::open(input)?);
+    let mut lines: Vec<String> = vec![];
+    for line in reader.lines() {
+        lines.push(line?);
+    }
+    let cards = parse_cards(&lines)?;
+    let stats = card_stats(&cards)?;
+    println!("{:<8} {:>8} {:>8}", "matches", "cards", "share");
+    for (matches, count) in &stats.match_counts {
+        println!("{:<8} {:>8} {:>7.2}%", matches, count, 100.0 * *count as f64 / stats.cards as f64);
+    }
+    println!("Cards: {}", stats.cards);
+    println!("Total points: {}", stats.total_points);
+    println!("Expected points per card: {:.3}", stats.total_points as f64 / stats.cards.max(1) as f64);
+    match stats.total_instances {
+        Ok(total_instances) => println!("Total scratchcards: {}", total_instances),
+        Err(e) => println!("Total scratchcards: {}", e),
+    }
+    Ok(())
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct CardStats {
+    cards: usize,
+    // How many cards have each number of matches
+    match_counts: BTreeMap<usize, usize>,
+    total_points: u64,
+    // Long cascades can overflow even when the points don't
+    total_instances: Result<u64, String>,
+}
+
+fn card_stats(cards: &[Card]) -> Result<CardStats, String> {
+    let mut match_counts = BTreeMap::<usize, usize>::new();
+    let mut total_points: u64 = 0;
+    for card in cards {
+        *match_counts.entry(card.matching_nums.len()).or_default() += 1;
+        total_points = total_points.checked_add(count_points(card)?)
+            .ok_or("The sum of card points doesn't fit in a u64")?;
+    }
+    let total_instances = card_instances(cards).and_then(|instances| total_instances(&instances));
+    Ok(CardStats { cards: cards.len(), match_counts, total_points, total_instances })
+}
+
+#[test]
+fn test_card_stats() {
+    let lines = vec![
+        String::from("Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53"),
+        String::from("Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19"),
+        String::from("Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1"),
+        String::from("Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83"),
+        String::from("Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36"),
+        String::from("Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11"),
+    ];
+    let stats = card_stats(&parse_cards(&lines).unwrap()).unwrap();
+    assert_eq!(
+        CardStats {
+            cards: 6,
+            match_counts: BTreeMap::from([(0, 2), (1, 1), (2, 2), (4, 1)]),
+            total_points: 13,
+            total_instances: Ok(30),
+        },
+        stats
+    );
+
+    // Random cards match often enough for the copies to overflow
+    let generator = CardGenerator::new(10, 25, "1-99").unwrap();
+    let stats = card_stats(&parse_cards(&generator.generate(2000, 7)).unwrap()).unwrap();
+    assert_eq!(2000, stats.match_counts.values().sum::<usize>());
+    assert_eq!(Err(String::from("Card 82 ends up with more than 18446744073709551615 instances")), stats.total_instances);
 }
@@ -1,39 +1,22 @@
 use std::collections::{HashMap, VecDeque};
+use std::error::Error;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
+use std::sync::OnceLock;
 use regex::Regex;
 use num_integer::Integer;
 
-pub fn run_day_8_part_1() -> () {
+pub fn run_day_8_part_1() -> Result<(), Box<dyn Error>> {
     let file: File = File::open("inputs/day8.txt").expect("Failed to open file");
     let reader = BufReader::new(file);
     let mut lines: VecDeque<String> = reader.lines().map(|l| l.expect("Failed to read line")).collect();
     let moves = lines.pop_front().unwrap();
     lines.pop_front(); // Drop the empty line below the moves 
-    let hashmap = parse_lines_as_node_hashmap(Vec::<String>::from(lines));
-    let mut steps = 0;
-    let mut current = hashmap.get("AAA").unwrap();
-    loop {
-        if current.val == "ZZZ" {
-            break
-        }
-        for move_chr in moves.chars() {
-            if current.val == "ZZZ" {
-                break
-            }
-            steps += 1;
-            match move_chr {
-            'L' => {
-                    current = hashmap.get(&current.left).unwrap();
-            },
-            'R' => {
-                    current = hashmap.get(&current.right).unwrap();
-            },
-            _ => panic!("Invalid move!")
-            }
-        }
-    }
+    let hashmap = parse_lines_as_node_hashmap(Vec::<String>::from(lines))?;
+    let network = Network::from_nodes(&hashmap)?;
+    let steps = steps_from_aaa_to_zzz(&parse_moves(&moves)?, &network)?;
     println!("Part 1: Steps to go from AAA to ZZZ ==> {}", steps);
+    Ok(())
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -43,16 +26,23 @@ struct Node {
     left: String,
 }
 
-fn parse_line_as_node(line: String) -> Node {
-    let re = Regex::new("[A-Z]{3}").unwrap();
-    let vals: Vec<String> = re.find_iter(&line).map(|m| String::from(m.as_str())).collect();
-    let node_val = vals.get(0).expect("There should be a val here").to_string();
-    let node = Node {
-        val: node_val,
-        left: vals.get(1).expect("There should be a val here").to_string(),
-        right: vals.get(2).expect("There should be a val here").to_string(),
+// Node lines look like "AAA = (BBB, CCC)". Labels can be any run of letters and digits,
+// the part 2 example uses labels like "11A" and "22Z".
+fn parse_line_as_node(line: String) -> Result<Node, String> {
+    // Compiled once, generated networks can have a lot of lines
+    static RE: OnceLock<Regex> = OnceLock::new();
+    let re = RE.get_or_init(|| {
+        Regex::new(r"^\s*([[:alnum:]]+)\s*=\s*\(\s*([[:alnum:]]+)\s*,\s*([[:alnum:]]+)\s*\)\s*$").unwrap()
+    });
+    let Some(captures) = re.captures(&line) else {
+        return Err(format!("Expected a node like \"AAA = (BBB, CCC)\", found {:?}", line))
     };
-    node
+    let (_, [val, left, right]) = captures.extract();
+    Ok(Node {
+        val: String::from(val),
+        left: String::from(left),
+        right: String::from(right),
+    })
 }
 
 #[test]
@@ -63,16 +53,48 @@ fn test_parse_line_as_node() {
         left: String::from("BBB"),
         right: String::from("CCC"),
     };
-    assert_eq!(parse_line_as_node(line), node)
+    assert_eq!(parse_line_as_node(line), Ok(node))
+}
+
+#[test]
+fn test_parse_line_as_node_alphanumeric() {
+    let line = String::from("11A = (11B, XXX)");
+    let node = Node {
+        val: String::from("11A"),
+        left: String::from("11B"),
+        right: String::from("XXX"),
+    };
+    assert_eq!(parse_line_as_node(line), Ok(node));
+
+    let line = String::from("START = (a1, LONGERLABEL2)");
+    let node = Node {
+        val: String::from("START"),
+        left: String::from("a1"),
+        right: String::from("LONGERLABEL2"),
+    };
+    assert_eq!(parse_line_as_node(line), Ok(node));
+}
+
+#[test]
+fn test_parse_line_as_node_malformed() {
+    assert!(parse_line_as_node(String::from("AAA = (BBB)")).is_err());
+    assert!(parse_line_as_node(String::from("AAA = BBB, CCC")).is_err());
+    assert!(parse_line_as_node(String::from("AAA (BBB, CCC)")).is_err());
+    assert!(parse_line_as_node(String::from("AAA = (BBB, CCC, DDD)")).is_err());
+    assert!(parse_line_as_node(String::from("A-A = (BBB, CCC)")).is_err());
 }
 
-fn parse_lines_as_node_hashmap(lines: Vec<String>) -> HashMap<String, Node> {
+fn parse_lines_as_node_hashmap(lines: Vec<String>) -> Result<HashMap<String, Node>, String> {
     let mut hashmap = HashMap::<String, Node>::new();
-    for line in lines {
-        let node = parse_line_as_node(line);
+    for (idx, line) in lines.into_iter().enumerate() {
+        if line.trim().is_empty() {
+            continue
+        }
+        // +3 to account for the moves and the empty line above the nodes
+        let node = parse_line_as_node(line).map_err(|e| format!("Line {}: {}", idx + 3, e))?;
         hashmap.insert(node.val.clone(), node);
     }
-    hashmap
+    Ok(hashmap)
 }
 
 #[test]
@@ -100,52 +122,631 @@ fn test_parse_lines_as_node_hashmap() {
     );
     assert_eq!(
         parse_lines_as_node_hashmap(lines),
-        hashmap
+        Ok(hashmap)
     )
 }
 
+#[test]
+fn test_parse_lines_as_node_hashmap_malformed() {
+    let lines = vec![
+        String::from("AAA = (BBB, CCC)"),
+        String::from("BBB = (DDD EEE)")
+    ];
+    assert_eq!(
+        parse_lines_as_node_hashmap(lines),
+        Err(String::from("Line 4: Expected a node like \"AAA = (BBB, CCC)\", found \"BBB = (DDD EEE)\""))
+    )
+}
+
+// Walking the HashMap<String, Node> means hashing and cloning a String on every step.
+// Instead, every label gets an index (its position in the sorted labels) and the
+// network becomes a Vec of [left, right] indices.
+#[derive(Debug, PartialEq, Eq)]
+struct Network {
+    labels: Vec<String>,
+    edges: Vec<[u32; 2]>,
+}
+
+impl Network {
+    fn from_nodes(hashmap: &HashMap<String, Node>) -> Result<Self, String> {
+        let mut labels: Vec<String> = hashmap.keys().cloned().collect();
+        labels.sort();
+        let indices: HashMap<&str, u32> = labels.iter().enumerate().map(|(idx, label)| (label.as_str(), idx as u32)).collect();
+        let mut edges: Vec<[u32; 2]> = vec![];
+        let mut dangling: Vec<String> = vec![];
+        for label in &labels {
+            let node = hashmap.get(label).unwrap();
+            let mut edge = [0, 0];
+            for (move_idx, (move_chr, next)) in [('L', &node.left), ('R', &node.right)].into_iter().enumerate() {
+                match indices.get(next.as_str()) {
+                    Some(next_idx) => edge[move_idx] = *next_idx,
+                    None => dangling.push(format!("{} ({}) -> {}", label, move_chr, next)),
+                }
+            }
+            edges.push(edge);
+        }
+        if !dangling.is_empty() {
+            return Err(format!("The network references nodes that don't exist: {}", dangling.join(", ")))
+        }
+        Ok(Self { labels, edges })
+    }
+
+    fn index_of(&self, label: &str) -> Option<u32> {
+        self.labels.binary_search_by(|l| l.as_str().cmp(label)).ok().map(|idx| idx as u32)
+    }
+
+    fn label(&self, idx: u32) -> &str {
+        &self.labels[idx as usize]
+    }
+
+    // move_idx is 0 for L and 1 for R, see parse_moves
+    fn next(&self, idx: u32, move_idx: usize) -> u32 {
+        self.edges[idx as usize][move_idx]
+    }
+}
+
+#[test]
+fn test_network_from_nodes() {
+    let lines = vec![
+        String::from("BBB = (AAA, ZZZ)"),
+        String::from("AAA = (BBB, BBB)"),
+        String::from("ZZZ = (ZZZ, ZZZ)"),
+    ];
+    let network = Network::from_nodes(&parse_lines_as_node_hashmap(lines).unwrap()).unwrap();
+    assert_eq!(
+        Network {
+            labels: vec![String::from("AAA"), String::from("BBB"), String::from("ZZZ")],
+            edges: vec![[1, 1], [0, 2], [2, 2]],
+        },
+        network
+    );
+    assert_eq!(Some(1), network.index_of("BBB"));
+    assert_eq!(None, network.index_of("CCC"));
+    assert_eq!("ZZZ", network.label(network.next(1, 1)));
+}
+
+#[test]
+fn test_network_from_nodes_dangling() {
+    let lines = vec![
+        String::from("AAA = (BBB, QQQ)"),
+        String::from("BBB = (RRR, AAA)"),
+    ];
+    assert_eq!(
+        Err(String::from("The network references nodes that don't exist: AAA (R) -> QQQ, BBB (L) -> RRR")),
+        Network::from_nodes(&parse_lines_as_node_hashmap(lines).unwrap())
+    );
+}
+
+// Turn the instructions into edge indices, 0 for L and 1 for R
+fn parse_moves(moves: &str) -> Result<Vec<usize>, String> {
+    if moves.trim().is_empty() {
+        return Err(String::from("Expected at least one move"))
+    }
+    moves.trim().chars().map(|chr| match chr {
+        'L' => Ok(0),
+        'R' => Ok(1),
+        _ => Err(format!("Expected the moves to be 'L' or 'R', found {:?}", chr)),
+    }).collect()
+}
+
+// The walk is deterministic, and where it goes next only depends on the current node and how far
+// along the moves we are. So there are only nodes * moves states, and once one of them comes up
+// a second time the walk is going around in circles and won't find anything new.
+struct VisitedStates {
+    bits: Vec<u64>,
+    moves_len: usize,
+}
+
+impl VisitedStates {
+    fn new(network: &Network, moves_len: usize) -> Self {
+        let state_count = network.labels.len() * moves_len;
+        Self {
+            bits: vec![0; state_count.div_ceil(64)],
+            moves_len,
+        }
+    }
+
+    // Like HashSet::insert, returns false if the state was already visited
+    fn insert(&mut self, node: u32, move_idx: usize) -> bool {
+        let state = node as usize * self.moves_len + move_idx;
+        let mask = 1 << (state % 64);
+        let visited = self.bits[state / 64] & mask != 0;
+        self.bits[state / 64] |= mask;
+        !visited
+    }
+}
+
+fn steps_from_aaa_to_zzz(moves: &[usize], network: &Network) -> Result<usize, String> {
+    let end = network.index_of("ZZZ").ok_or("There's no ZZZ node to reach")?;
+    let mut current = network.index_of("AAA").ok_or("There's no AAA node to start from")?;
+    let mut visited = VisitedStates::new(network, moves.len());
+    let mut steps = 0;
+    for (move_idx, next_move) in moves.iter().enumerate().cycle() {
+        if current == end {
+            break
+        }
+        if !visited.insert(current, move_idx) {
+            return Err(format!("ZZZ unreachable from AAA after exploring {} states", steps))
+        }
+        current = network.next(current, *next_move);
+        steps += 1;
+    }
+    Ok(steps)
+}
+
+#[test]
+fn test_steps_from_aaa_to_zzz() {
+    let lines = vec![
+        String::from("AAA = (BBB, CCC)"),
+        String::from("BBB = (DDD, EEE)"),
+        String::from("CCC = (ZZZ, GGG)"),
+        String::from("DDD = (DDD, DDD)"),
+        String::from("EEE = (EEE, EEE)"),
+        String::from("GGG = (GGG, GGG)"),
+        String::from("ZZZ = (ZZZ, ZZZ)"),
+    ];
+    let network = Network::from_nodes(&parse_lines_as_node_hashmap(lines).unwrap()).unwrap();
+    assert_eq!(Ok(2), steps_from_aaa_to_zzz(&parse_moves("RL").unwrap(), &network));
+
+    let lines = vec![
+        String::from("AAA = (BBB, BBB)"),
+        String::from("BBB = (AAA, ZZZ)"),
+        String::from("ZZZ = (ZZZ, ZZZ)"),
+    ];
+    let network = Network::from_nodes(&parse_lines_as_node_hashmap(lines).unwrap()).unwrap();
+    assert_eq!(Ok(6), steps_from_aaa_to_zzz(&parse_moves("LLR").unwrap(), &network));
+}
+
+#[test]
+fn test_steps_from_aaa_to_zzz_unreachable() {
+    // AAA -> BBB -> CCC -> BBB ... never gets to ZZZ
+    let lines = vec![
+        String::from("AAA = (BBB, BBB)"),
+        String::from("BBB = (CCC, CCC)"),
+        String::from("CCC = (BBB, BBB)"),
+        String::from("ZZZ = (ZZZ, ZZZ)"),
+    ];
+    let network = Network::from_nodes(&parse_lines_as_node_hashmap(lines).unwrap()).unwrap();
+    // (AAA, 0) (BBB, 1) (CCC, 0) (BBB, 1) repeats
+    assert_eq!(
+        Err(String::from("ZZZ unreachable from AAA after exploring 3 states")),
+        steps_from_aaa_to_zzz(&parse_moves("LR").unwrap(), &network)
+    );
+
+    let lines = vec![
+        String::from("AAA = (AAA, AAA)"),
+    ];
+    let network = Network::from_nodes(&parse_lines_as_node_hashmap(lines).unwrap()).unwrap();
+    assert_eq!(
+        Err(String::from("There's no ZZZ node to reach")),
+        steps_from_aaa_to_zzz(&parse_moves("L").unwrap(), &network)
+    );
+}
+
 // I had to look up the trick for this one.
 // The trick: for each starting point of **A, we get the number of steps required to arrive
 // a **Z. Then, we calculate the least common multiple of each of those, and that's the answer!
-pub fn run_day_8_part_2() -> () {
+pub fn run_day_8_part_2() -> Result<(), Box<dyn Error>> {
     let file: File = File::open("inputs/day8.txt").expect("Failed to open file");
     let reader = BufReader::new(file);
     let mut lines: VecDeque<String> = reader.lines().map(|l| l.expect("Failed to read line")).collect();
     let moves = lines.pop_front().unwrap();
     lines.pop_front(); // Drop the empty line below the moves 
-    let hashmap = parse_lines_as_node_hashmap(Vec::<String>::from(lines));
-    let mut current_vals = Vec::new();
-    for node in hashmap.values() {
-        if node.val.ends_with('A') {
-            current_vals.push(node.val.clone());
-        }
-    }
+    let hashmap = parse_lines_as_node_hashmap(Vec::<String>::from(lines))?;
+    let network = Network::from_nodes(&hashmap)?;
+    println!("Part 2: Steps to go from **A to **Z ==> {}", ghost_steps(&parse_moves(&moves)?, &network)?);
+    Ok(())
+}
 
+fn ghost_steps(moves: &[usize], network: &Network) -> Result<usize, String> {
+    let is_end: Vec<bool> = network.labels.iter().map(|label| label.ends_with('Z')).collect();
     let mut steps: Vec::<usize> = vec![];
-    for val in current_vals {
-        let mut current = val.clone();
+    for (start, label) in network.labels.iter().enumerate() {
+        if !label.ends_with('A') {
+            continue
+        }
+        let mut visited = VisitedStates::new(network, moves.len());
+        let mut current = start as u32;
         let mut step = 0;
-        while !current.ends_with('Z') {
-            for l_or_r in moves.chars() {
-                let node = hashmap.get(&current).expect("Expeced a node here!");
-                match l_or_r {
-                    'R' => {
-                        current = node.right.clone();
-                    },
-                    'L' => {
-                        current = node.left.clone();
-                    }
-                _ => panic!("Expected this to be 'L' or 'R'" )
-                }
-                step += 1;
+        // Stop at the first **Z, even partway through the moves
+        for (move_idx, next_move) in moves.iter().enumerate().cycle() {
+            if is_end[current as usize] {
+                break
+            }
+            if !visited.insert(current, move_idx) {
+                return Err(format!("No **Z reachable from {} after exploring {} states", label, step))
             }
+            current = network.next(current, *next_move);
+            step += 1;
         }
         steps.push(step);
     }
+    Ok(lcm_of_vec(&steps))
+}
 
-    println!("Part 2: Steps to go from **A to **Z ==> {}", lcm_of_vec(&steps));
+#[test]
+fn test_ghost_steps() {
+    let lines = vec![
+        String::from("11A = (11B, XXX)"),
+        String::from("11B = (XXX, 11Z)"),
+        String::from("11Z = (11B, XXX)"),
+        String::from("22A = (22B, XXX)"),
+        String::from("22B = (22C, 22C)"),
+        String::from("22C = (22Z, 22Z)"),
+        String::from("22Z = (22B, 22B)"),
+        String::from("XXX = (XXX, XXX)"),
+    ];
+    let network = Network::from_nodes(&parse_lines_as_node_hashmap(lines).unwrap()).unwrap();
+    assert_eq!(Ok(6), ghost_steps(&parse_moves("LR").unwrap(), &network));
+}
+
+#[test]
+fn test_ghost_steps_mid_pass() {
+    // 11Z comes up after the first of the two moves, and every state after that repeats
+    let lines = vec![
+        String::from("11A = (11Z, 11Z)"),
+        String::from("11Z = (11A, 11A)"),
+    ];
+    let network = Network::from_nodes(&parse_lines_as_node_hashmap(lines).unwrap()).unwrap();
+    assert_eq!(Ok(1), ghost_steps(&parse_moves("LL").unwrap(), &network));
+}
+
+#[test]
+fn test_ghost_steps_unreachable() {
+    let lines = vec![
+        String::from("11A = (11B, 11B)"),
+        String::from("11B = (11Z, 11Z)"),
+        String::from("11Z = (11Z, 11Z)"),
+        String::from("22A = (22B, 22B)"),
+        String::from("22B = (22A, 22A)"),
+    ];
+    let network = Network::from_nodes(&parse_lines_as_node_hashmap(lines).unwrap()).unwrap();
+    assert_eq!(
+        Err(String::from("No **Z reachable from 22A after exploring 2 states")),
+        ghost_steps(&parse_moves("L").unwrap(), &network)
+    );
 }
 
 fn lcm_of_vec(values: &[usize]) -> usize {
     values.iter().cloned().reduce(|a, b| a.lcm(&b)).unwrap_or(1)
 }
+
+// Diagnostics for the part 2 trick.
+// lcm_of_vec only gives the right answer when every ghost's walk is a clean loop:
+// the first **Z is reached after exactly one cycle, it's the only **Z on the loop,
+// and the cycle lines up with the end of the instructions.
+pub fn run_day_8_lcm_check() -> Result<(), Box<dyn Error>> {
+    let file: File = File::open("inputs/day8.txt").expect("Failed to open file");
+    let reader = BufReader::new(file);
+    let mut lines: VecDeque<String> = reader.lines().map(|l| l.expect("Failed to read line")).collect();
+    let moves = lines.pop_front().unwrap();
+    lines.pop_front(); // Drop the empty line below the moves
+    let hashmap = parse_lines_as_node_hashmap(Vec::<String>::from(lines))?;
+    let network = Network::from_nodes(&hashmap)?;
+    let moves = parse_moves(&moves)?;
+    let cycles: Vec<GhostCycle> = (0..network.labels.len() as u32)
+        .filter(|idx| network.label(*idx).ends_with('A'))
+        .map(|start| trace_ghost(start, &moves, &network))
+        .collect();
+    println!("{:<8} {:>10} {:>12} {:>10} {:>14} {:>10} {:>14}", "start", "first Z", "cycle", "Z visits", "first Z=cycle", "single Z", "multiple of N");
+    for cycle in &cycles {
+        let first_z = cycle.first_z_steps.map(|s| s.to_string()).unwrap_or(String::from("never"));
+        println!(
+            "{:<8} {:>10} {:>12} {:>10} {:>14} {:>10} {:>14}",
+            cycle.start,
+            first_z,
+            format!("{}+{}", cycle.cycle_start, cycle.cycle_length),
+            cycle.z_steps.len(),
+            yes_or_no(cycle.first_z_equals_cycle()),
+            yes_or_no(cycle.visits_single_z()),
+            yes_or_no(cycle.first_z_multiple_of(moves.len())),
+        );
+    }
+    println!("(N = {} instructions, cycle is shown as <steps before the loop>+<loop length>)", moves.len());
+    if cycles.iter().all(|c| c.lcm_assumptions_hold(moves.len())) {
+        println!("lcm_of_vec is trustworthy for inputs/day8.txt");
+    } else {
+        println!("lcm_of_vec is NOT trustworthy for inputs/day8.txt, see the \"no\" columns above");
+    }
+    Ok(())
+}
+
+fn yes_or_no(value: bool) -> &'static str {
+    if value { "yes" } else { "no" }
+}
+
+// The walk of a single ghost, followed until it repeats a (node, instruction index) state.
+// Since there are only nodes * instructions states, that always happens eventually.
+#[derive(Debug, PartialEq, Eq)]
+struct GhostCycle {
+    start: String,
+    first_z_steps: Option<usize>,
+    // Steps taken before entering the loop, and the length of the loop
+    cycle_start: usize,
+    cycle_length: usize,
+    // Every step count at which the ghost stood on a **Z node before the walk repeated
+    z_steps: Vec<usize>,
+}
+
+impl GhostCycle {
+    fn first_z_equals_cycle(&self) -> bool {
+        self.first_z_steps == Some(self.cycle_length)
+    }
+
+    fn visits_single_z(&self) -> bool {
+        self.z_steps.len() == 1
+    }
+
+    fn first_z_multiple_of(&self, moves_len: usize) -> bool {
+        match self.first_z_steps {
+            Some(steps) => steps % moves_len == 0,
+            None => false,
+        }
+    }
+
+    fn lcm_assumptions_hold(&self, moves_len: usize) -> bool {
+        self.first_z_equals_cycle() && self.visits_single_z() && self.first_z_multiple_of(moves_len)
+    }
+}
+
+fn trace_ghost(start: u32, moves: &[usize], network: &Network) -> GhostCycle {
+    // The step at which each (node, move index) state was first seen, laid out node by node
+    let mut seen: Vec<Option<usize>> = vec![None; network.labels.len() * moves.len()];
+    let mut z_steps: Vec<usize> = vec![];
+    let mut current = start;
+    let mut step = 0;
+    loop {
+        let move_idx = step % moves.len();
+        let state = current as usize * moves.len() + move_idx;
+        if let Some(first_seen) = seen[state] {
+            return GhostCycle {
+                start: String::from(network.label(start)),
+                first_z_steps: z_steps.first().cloned(),
+                cycle_start: first_seen,
+                cycle_length: step - first_seen,
+                z_steps,
+            }
+        }
+        seen[state] = Some(step);
+        if step > 0 && network.label(current).ends_with('Z') {
+            z_steps.push(step);
+        }
+        current = network.next(current, moves[move_idx]);
+        step += 1;
+    }
+}
+
+#[test]
+fn test_trace_ghost() {
+    // The part 2 example, with the digits swapped for letters
+    let lines = vec![
+        String::from("AAA = (AAB, XXX)"),
+        String::from("AAB = (XXX, AAZ)"),
+        String::from("AAZ = (AAB, XXX)"),
+        String::from("BBA = (BBB, XXX)"),
+        String::from("BBB = (BBC, BBC)"),
+        String::from("BBC = (BBZ, BBZ)"),
+        String::from("BBZ = (BBB, BBB)"),
+        String::from("XXX = (XXX, XXX)"),
+    ];
+    let network = Network::from_nodes(&parse_lines_as_node_hashmap(lines).unwrap()).unwrap();
+    let moves = parse_moves("LR").unwrap();
+
+    let clean = trace_ghost(network.index_of("AAA").unwrap(), &moves, &network);
+    assert_eq!(
+        GhostCycle {
+            start: String::from("AAA"),
+            first_z_steps: Some(2),
+            cycle_start: 1,
+            cycle_length: 2,
+            z_steps: vec![2],
+        },
+        clean
+    );
+    assert!(clean.lcm_assumptions_hold(2));
+
+    let messy = trace_ghost(network.index_of("BBA").unwrap(), &moves, &network);
+    assert_eq!(
+        GhostCycle {
+            start: String::from("BBA"),
+            first_z_steps: Some(3),
+            cycle_start: 1,
+            cycle_length: 6,
+            z_steps: vec![3, 6],
+        },
+        messy
+    );
+    assert!(!messy.first_z_equals_cycle());
+    assert!(!messy.visits_single_z());
+    assert!(!messy.first_z_multiple_of(2));
+}
+
+// Graphviz export of the network, to see the ghost cycles:
+// $ cargo run -- --day 8 --part 1 --dot --components | dot -Tsvg > day8.svg
+pub fn run_day_8_dot(color_components: bool) -> Result<(), Box<dyn Error>> {
+    let file: File = File::open("inputs/day8.txt").expect("Failed to open file");
+    let reader = BufReader::new(file);
+    let mut lines: VecDeque<String> = reader.lines().map(|l| l.expect("Failed to read line")).collect();
+    lines.pop_front(); // The moves don't matter for the shape of the network
+    lines.pop_front(); // Drop the empty line below the moves
+    let hashmap = parse_lines_as_node_hashmap(Vec::<String>::from(lines))?;
+    print!("{}", network_to_dot(&hashmap, color_components));
+    Ok(())
+}
+
+// Fill colours handed out to components, round robin
+const COMPONENT_COLORS: [&str; 8] = [
+    "lightblue", "lightpink", "palegreen", "khaki", "plum", "lightsalmon", "paleturquoise", "wheat",
+];
+
+fn network_to_dot(hashmap: &HashMap<String, Node>, color_components: bool) -> String {
+    let mut vals: Vec<&String> = hashmap.keys().collect();
+    vals.sort();
+    let components = connected_components(hashmap);
+    let mut dot = String::from("digraph network {\n");
+    for val in &vals {
+        let mut attrs: Vec<String> = vec![];
+        if val.ends_with('A') {
+            attrs.push(String::from("shape=box, color=forestgreen, penwidth=3"));
+        } else if val.ends_with('Z') {
+            attrs.push(String::from("shape=doublecircle, color=red, penwidth=3"));
+        }
+        if color_components {
+            let component = components.get(*val).unwrap();
+            let color = COMPONENT_COLORS[component % COMPONENT_COLORS.len()];
+            attrs.push(format!("style=filled, fillcolor={}", color));
+        }
+        if attrs.is_empty() {
+            dot.push_str(&format!("    \"{}\";\n", val));
+        } else {
+            dot.push_str(&format!("    \"{}\" [{}];\n", val, attrs.join(", ")));
+        }
+    }
+    for val in &vals {
+        let node = hashmap.get(*val).unwrap();
+        // Lots of nodes point both ways at the same node, draw those as one edge
+        if node.left == node.right {
+            dot.push_str(&format!("    \"{}\" -> \"{}\" [label=\"L,R\"];\n", node.val, node.left));
+        } else {
+            dot.push_str(&format!("    \"{}\" -> \"{}\" [label=\"L\"];\n", node.val, node.left));
+            dot.push_str(&format!("    \"{}\" -> \"{}\" [label=\"R\"];\n", node.val, node.right));
+        }
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+#[test]
+fn test_network_to_dot() {
+    let lines = vec![
+        String::from("AAA = (BBB, CCC)"),
+        String::from("BBB = (ZZZ, ZZZ)"),
+        String::from("CCC = (ZZZ, AAA)"),
+        String::from("ZZZ = (ZZZ, ZZZ)"),
+    ];
+    let hashmap = parse_lines_as_node_hashmap(lines).unwrap();
+    assert_eq!(
+        "digraph network {
+    \"AAA\" [shape=box, color=forestgreen, penwidth=3];
+    \"BBB\";
+    \"CCC\";
+    \"ZZZ\" [shape=doublecircle, color=red, penwidth=3];
+    \"AAA\" -> \"BBB\" [label=\"L\"];
+    \"AAA\" -> \"CCC\" [label=\"R\"];
+    \"BBB\" -> \"ZZZ\" [label=\"L,R\"];
+    \"CCC\" -> \"ZZZ\" [label=\"L\"];
+    \"CCC\" -> \"AAA\" [label=\"R\"];
+    \"ZZZ\" -> \"ZZZ\" [label=\"L,R\"];
+}
+",
+        network_to_dot(&hashmap, false)
+    );
+}
+
+// Label every node with the index of its (weakly) connected component.
+// Components are numbered in label order, so the colouring is stable between runs.
+fn connected_components(hashmap: &HashMap<String, Node>) -> HashMap<String, usize> {
+    let mut neighbours = HashMap::<&str, Vec<&str>>::new();
+    for node in hashmap.values() {
+        for next in [&node.left, &node.right] {
+            neighbours.entry(node.val.as_str()).or_default().push(next.as_str());
+            neighbours.entry(next.as_str()).or_default().push(node.val.as_str());
+        }
+    }
+    let mut vals: Vec<&String> = hashmap.keys().collect();
+    vals.sort();
+    let mut components = HashMap::<String, usize>::new();
+    let mut component = 0;
+    for val in vals {
+        if components.contains_key(val) {
+            continue
+        }
+        let mut queue = VecDeque::from([val.as_str()]);
+        while let Some(current) = queue.pop_front() {
+            if components.contains_key(current) {
+                continue
+            }
+            components.insert(String::from(current), component);
+            for next in neighbours.get(current).into_iter().flatten() {
+                if !components.contains_key(*next) {
+                    queue.push_back(next);
+                }
+            }
+        }
+        component += 1;
+    }
+    components
+}
+
+#[test]
+fn test_connected_components() {
+    let lines = vec![
+        String::from("AAA = (AAB, XXX)"),
+        String::from("AAB = (XXX, AAZ)"),
+        String::from("AAZ = (AAB, XXX)"),
+        String::from("BBA = (BBB, BBB)"),
+        String::from("BBB = (BBZ, BBZ)"),
+        String::from("BBZ = (BBB, BBB)"),
+        String::from("XXX = (XXX, XXX)"),
+    ];
+    let components = connected_components(&parse_lines_as_node_hashmap(lines).unwrap());
+    for val in ["AAA", "AAB", "AAZ", "XXX"] {
+        assert_eq!(Some(&0), components.get(val));
+    }
+    for val in ["BBA", "BBB", "BBZ"] {
+        assert_eq!(Some(&1), components.get(val));
+    }
+}
+
+// Compares walking the String HashMap (how both parts used to work) with the interned Network.
+// $ cargo test --release bench_network -- --ignored --nocapture
+#[test]
+#[ignore]
+fn bench_network_vs_hashmap() {
+    use std::time::Instant;
+    use rand::rngs::StdRng;
+    use rand::seq::SliceRandom;
+    use rand::{Rng, SeedableRng};
+
+    // A chain of half a million nodes where L steps one node ahead and R steps two,
+    // with the labels shuffled so the chain order isn't the sorted order.
+    let node_count: usize = 500_000;
+    let mut rng = StdRng::seed_from_u64(2023);
+    let mut labels: Vec<String> = (0..node_count).map(|idx| format!("N{}", idx)).collect();
+    labels.shuffle(&mut rng);
+    labels[0] = String::from("AAA");
+    labels[node_count - 1] = String::from("ZZZ");
+    let lines: Vec<String> = (0..node_count).map(|idx| {
+        let left = (idx + 1).min(node_count - 1);
+        let right = (idx + 2).min(node_count - 1);
+        format!("{} = ({}, {})", labels[idx], labels[left], labels[right])
+    }).collect();
+    let moves: String = (0..293).map(|_| if rng.gen_bool(0.5) { 'L' } else { 'R' }).collect();
+    let hashmap = parse_lines_as_node_hashmap(lines).unwrap();
+
+    let started = Instant::now();
+    let mut hashmap_steps = 0;
+    let mut current = String::from("AAA");
+    for move_chr in moves.chars().cycle() {
+        if current == "ZZZ" {
+            break
+        }
+        let node = hashmap.get(&current).unwrap();
+        current = if move_chr == 'L' { node.left.clone() } else { node.right.clone() };
+        hashmap_steps += 1;
+    }
+    let hashmap_time = started.elapsed();
+
+    let started = Instant::now();
+    let network = Network::from_nodes(&hashmap).unwrap();
+    let compile_time = started.elapsed();
+    let started = Instant::now();
+    let network_steps = steps_from_aaa_to_zzz(&parse_moves(&moves).unwrap(), &network).unwrap();
+    let network_time = started.elapsed();
+
+    assert_eq!(hashmap_steps, network_steps);
+    println!("{} steps over {} nodes", network_steps, node_count);
+    println!("HashMap<String, Node> walk: {:?}", hashmap_time);
+    println!("Network walk:               {:?} (+ {:?} to build the Network)", network_time, compile_time);
+    println!("Speedup of the walk:        {:.1}x", hashmap_time.as_secs_f64() / network_time.as_secs_f64());
+}
@@ -1,113 +1,341 @@
-use std::collections::VecDeque;
+use std::collections::BTreeMap;
+use std::error::Error;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
+use num_rational::Ratio;
+use num_traits::{CheckedAdd, CheckedSub};
 
-pub fn run_day_9_part_1() -> () {
+pub fn run_day_9_part_1() -> Result<(), Box<dyn Error>> {
     let file: File = File::open("inputs/day9.txt").expect("Failed to open file");
     let reader = BufReader::new(file);
     let lines: Vec<String> = reader.lines().map(|l| l.expect("Failed to read line")).collect();
-    let histories: Vec<Vec<isize>> = lines.into_iter().map(|l|
-        l.split(" ").into_iter().map(|s|
-            s.parse::<isize>().expect("Failed to parse integer!")
-        ).collect()
-    ).collect();
-    let values: Vec<isize> = histories.into_iter().map(|hist| calculate_next_value(hist)).collect();
-    let answer = values.into_iter().reduce(|a, b| a + b).expect("Expected to find an answer!");
+    let answer = sum_of_extrapolated_values(&lines, calculate_next_value)?;
     println!("Part 1: The sum of the next values in the histories in inputs/day9.txt ==> {}", answer);
+    Ok(())
 }
 
-pub fn run_day_9_part_2() -> () {
+pub fn run_day_9_part_2() -> Result<(), Box<dyn Error>> {
     let file: File = File::open("inputs/day9.txt").expect("Failed to open file");
     let reader = BufReader::new(file);
     let lines: Vec<String> = reader.lines().map(|l| l.expect("Failed to read line")).collect();
-    let histories: Vec<Vec<isize>> = lines.into_iter().map(|l|
-        l.split(" ").into_iter().map(|s|
-            s.parse::<isize>().expect("Failed to parse integer!")
-        ).collect()
-    ).collect();
-    let values: Vec<isize> = histories.into_iter().map(|hist| calculate_preceding_value(hist)).collect();
-    let answer = values.into_iter().reduce(|a, b| a + b).expect("Expected to find an answer!");
+    let answer = sum_of_extrapolated_values(&lines, calculate_preceding_value)?;
     println!("Part 2: The sum of the next values in the histories in inputs/day9.txt ==> {}", answer);
+    Ok(())
 }
 
-fn calculate_next_value(history: Vec<isize>) -> isize {
-    let mut diffs: Vec<isize> = vec![]; 
-    let mut only_zeros = false;
-    let mut last_diff = history.clone();
-    // Calculate next number of history using the algorithm outlined in the directions...
-    // We calculate the diff of each item in the history
-    // Then the diffs of those values... until we arrive at a row of all zeros
-    while !only_zeros {
-        only_zeros = true;
-        let mut idx = 0;
-        let mut new_diffs: Vec<isize> = vec![];
-        for num in &last_diff {
-            if idx == 0 {
-                idx += 1;
-                continue;
+// Adds up the value extrapolate works out for every history, which can overflow
+// even when each of the values fits
+fn sum_of_extrapolated_values(lines: &[String], extrapolate: fn(Vec<isize>) -> Result<isize, String>) -> Result<isize, String> {
+    let mut answer: isize = 0;
+    for (idx, line) in lines.iter().enumerate() {
+        let history = parse_history(line).map_err(|e| format!("Line {}: {}", idx + 1, e))?;
+        let value = extrapolate(history).map_err(|e| format!("Line {}: {}", idx + 1, e))?;
+        answer = answer.checked_add(value)
+            .ok_or_else(|| format!("Line {}: The sum of the values so far doesn't fit in an isize", idx + 1))?;
+    }
+    Ok(answer)
+}
+
+#[test]
+fn test_sum_of_extrapolated_values() {
+    let lines = vec![
+        String::from("0 3 6 9 12 15"),
+        String::from("1 3 6 10 15 21"),
+        String::from("10 13 16 21 30 45"),
+    ];
+    assert_eq!(Ok(114), sum_of_extrapolated_values(&lines, calculate_next_value));
+    assert_eq!(Ok(2), sum_of_extrapolated_values(&lines, calculate_preceding_value));
+
+    // Each next value fits, but not the two of them together
+    let big = isize::MAX - 10;
+    let lines = vec![
+        format!("{} {} {}", big - 2, big - 1, big),
+        format!("{} {} {}", big - 2, big - 1, big),
+    ];
+    assert_eq!(Ok(big + 1), sum_of_extrapolated_values(&lines[..1], calculate_next_value));
+    assert_eq!(
+        Err(String::from("Line 2: The sum of the values so far doesn't fit in an isize")),
+        sum_of_extrapolated_values(&lines, calculate_next_value)
+    );
+}
+
+fn parse_history(line: &str) -> Result<Vec<isize>, String> {
+    line.split_whitespace()
+        .map(|s| s.parse::<isize>().map_err(|_| format!("Expected an integer, found {:?}", s)))
+        .collect()
+}
+
+#[test]
+fn test_parse_history() {
+    assert_eq!(Ok(vec![0, -3, 6]), parse_history("0 -3 6"));
+    assert_eq!(Ok(vec![]), parse_history(""));
+    assert_eq!(Err(String::from("Expected an integer, found \"x\"")), parse_history("1 x 3"));
+}
+
+// What each history actually is:
+// $ cargo run -- --day 9 --part 1 --closed-form
+pub fn run_day_9_closed_forms() -> Result<(), Box<dyn Error>> {
+    let file: File = File::open("inputs/day9.txt").expect("Failed to open file");
+    let reader = BufReader::new(file);
+    let lines: Vec<String> = reader.lines().map(|l| l.expect("Failed to read line")).collect();
+    let mut histories_per_degree = BTreeMap::<usize, usize>::new();
+    let mut never_constant = 0;
+    for (idx, line) in lines.iter().enumerate() {
+        let history = parse_history(line).map_err(|e| format!("Line {}: {}", idx + 1, e))?;
+        let polynomial = NewtonPolynomial::fit(&history).map_err(|e| format!("Line {}: {}", idx + 1, e))?;
+        let closed_form = polynomial.closed_form().map_err(|e| format!("Line {}: {}", idx + 1, e))?;
+        print!("Line {}: degree {}: p(n) = {}", idx + 1, polynomial.degree(), closed_form);
+        if polynomial.reached_zeros {
+            println!();
+        } else {
+            never_constant += 1;
+            println!(" (never reached a row of zeros, this only interpolates the {} values)", history.len());
+        }
+        *histories_per_degree.entry(polynomial.degree()).or_insert(0) += 1;
+    }
+    println!();
+    for (degree, count) in histories_per_degree {
+        println!("Degree {}: {} histories", degree, count);
+    }
+    println!("Never reached a row of zeros: {} histories", never_constant);
+    Ok(())
+}
+
+fn calculate_next_value(history: Vec<isize>) -> Result<isize, String> {
+    extrapolate(&history, history.len() as isize)
+}
+
+fn calculate_preceding_value(history: Vec<isize>) -> Result<isize, String> {
+    extrapolate(&history, -1)
+}
+
+// The directions only work out when the difference table ends in a row of zeros,
+// so anything else is an error rather than a guess:
+// - an empty history has nothing to extrapolate from
+// - a single value is taken to be constant, there are no differences to check
+// - otherwise if the rows run out first, the history never became constant
+fn extrapolate(history: &[isize], k: isize) -> Result<isize, String> {
+    if history.is_empty() {
+        return Err(String::from("Can't extrapolate an empty history"))
+    }
+    let polynomial = NewtonPolynomial::fit(history)?;
+    if !polynomial.reached_zeros {
+        return Err(format!("The differences of {:?} never become all zeros", history))
+    }
+    polynomial.value_at(k)
+}
+
+// The difference table from the directions is really fitting a polynomial to the history.
+// The first value of each row of the table (the forward differences d0, d1, d2, ...) is
+// all we need to know about it, since the value at index k of the history is
+//   d0 + d1 * C(k, 1) + d2 * C(k, 2) + ...
+// where C(k, j) = k * (k - 1) * ... * (k - j + 1) / j! also works for negative k.
+// So k = history.len() is the next value, k = -1 is the preceding value, and k = 1000 is fine too.
+#[derive(Debug, PartialEq, Eq)]
+struct NewtonPolynomial {
+    leading_diffs: Vec<isize>,
+    // Whether the difference table got down to a row of zeros. If it didn't, the polynomial
+    // is just the one of lowest degree going through every value of the history.
+    reached_zeros: bool,
+}
+
+impl NewtonPolynomial {
+    fn fit(history: &[isize]) -> Result<Self, String> {
+        // The zero polynomial would fit, but so would anything else
+        if history.is_empty() {
+            return Err(String::from("Can't fit a polynomial to an empty history"))
+        }
+        let mut leading_diffs: Vec<isize> = vec![];
+        // Each row of the difference table overwrites the one above it
+        let mut row = history.to_vec();
+        while !row.iter().all(|diff| *diff == 0) {
+            leading_diffs.push(row[0]);
+            for idx in 0..row.len() - 1 {
+                row[idx] = row[idx + 1].checked_sub(row[idx])
+                    .ok_or(format!("Overflow taking the differences of {:?}", history))?;
             }
-            let prev = last_diff.get(idx - 1).unwrap();
-            let diff = num - prev;
-            if diff != 0 {
-                only_zeros = false;
+            row.pop();
+        }
+        // The loop also stops once the rows run out, which isn't a row of zeros.
+        // A single value has no differences at all though, so it counts as constant.
+        let reached_zeros = !row.is_empty() || history.len() == 1;
+        Ok(Self { leading_diffs, reached_zeros })
+    }
+
+    // The row of the difference table that's constant, the zero polynomial counts as degree 0
+    fn degree(&self) -> usize {
+        self.leading_diffs.len().saturating_sub(1)
+    }
+
+    // Coefficients of the polynomial in the index n of the history, constant term first.
+    // C(n, j) = n * (n - 1) * ... * (n - j + 1) / j!, so multiply out the falling product
+    // one factor at a time and add each d_j / j! times it.
+    fn coefficients(&self) -> Result<Vec<Ratio<i128>>, String> {
+        // The falling factorials and factorials outgrow an i128 past about 33 values
+        let overflow = || format!("Overflow working out the closed form of a degree {} polynomial", self.degree());
+        let mut coefficients = vec![Ratio::from_integer(0); self.leading_diffs.len()];
+        let mut falling: Vec<i128> = vec![1];
+        let mut factorial: i128 = 1;
+        for (j, diff) in self.leading_diffs.iter().enumerate() {
+            if j > 0 {
+                let factor = j as i128 - 1;
+                let mut next: Vec<i128> = vec![0; falling.len() + 1];
+                for (power, coef) in falling.iter().enumerate() {
+                    next[power + 1] = next[power + 1].checked_add(*coef).ok_or_else(overflow)?;
+                    let product = coef.checked_mul(factor).ok_or_else(overflow)?;
+                    next[power] = next[power].checked_sub(product).ok_or_else(overflow)?;
+                }
+                falling = next;
+                factorial = factorial.checked_mul(j as i128).ok_or_else(overflow)?;
             }
-            new_diffs.push(diff);
-            if idx == last_diff.len() - 1 {
-                break;
+            for (power, coef) in falling.iter().enumerate() {
+                let numerator = (*diff as i128).checked_mul(*coef).ok_or_else(overflow)?;
+                let term = Ratio::new(numerator, factorial);
+                coefficients[power] = coefficients[power].checked_add(&term).ok_or_else(overflow)?;
             }
-            idx += 1;
         }
-        last_diff = new_diffs.clone();
-        diffs.push(new_diffs.pop().unwrap());
+        Ok(coefficients)
     }
-    diffs.push(*history.last().expect("Expected a value in given history!"));
-    let mut val = 0 as isize;
-    for diff in diffs.into_iter().rev() { 
-        val += diff
-    } 
-    val
-}
-
-fn calculate_preceding_value(history: Vec<isize>) -> isize {
-    let mut diffs: VecDeque<isize> = vec![].into(); 
-    let mut only_zeros = false;
-    let mut last_diff: VecDeque<isize> = history.clone().into();
-    // Calculate next number of history using the algorithm outlined in the directions...
-    // We calculate the diff of each item in the history
-    // Then the diffs of those values... until we arrive at a row of all zeros
-    while !only_zeros {
-        only_zeros = true;
-        let mut idx = last_diff.len() - 2;
-        let mut new_diffs: VecDeque<isize> = vec![].into();
-        loop {
-            let num = last_diff.get(idx).expect("Expeced value at this index!");
-            let next = last_diff.get(idx + 1).expect("Expeced value at this index!");
-            let diff = next - num;
-            if diff != 0 {
-                only_zeros = false;
+
+    // e.g. "1/2 n^2 + 3/2 n + 1"
+    fn closed_form(&self) -> Result<String, String> {
+        let mut closed_form = String::new();
+        for (power, coef) in self.coefficients()?.into_iter().enumerate().rev() {
+            if coef == Ratio::from_integer(0) {
+                continue
             }
-            new_diffs.push_front(diff);
-            if idx == 0 {
-                break;
+            let (sign, coef) = if coef < Ratio::from_integer(0) {
+                let negated = Ratio::from_integer(0).checked_sub(&coef)
+                    .ok_or_else(|| format!("Overflow writing out the closed form of a degree {} polynomial", self.degree()))?;
+                ("-", negated)
+            } else {
+                ("+", coef)
+            };
+            if closed_form.is_empty() {
+                if sign == "-" {
+                    closed_form.push('-');
+                }
+            } else {
+                closed_form.push_str(&format!(" {} ", sign));
             }
-            idx -= 1;
+            let variable = match power {
+                0 => String::new(),
+                1 => String::from("n"),
+                _ => format!("n^{}", power),
+            };
+            if power == 0 {
+                closed_form.push_str(&coef.to_string());
+            } else if coef == Ratio::from_integer(1) {
+                closed_form.push_str(&variable);
+            } else {
+                closed_form.push_str(&format!("{} {}", coef, variable));
+            }
+        }
+        if closed_form.is_empty() {
+            closed_form.push('0');
+        }
+        Ok(closed_form)
+    }
+
+    fn value_at(&self, k: isize) -> Result<isize, String> {
+        let overflow = || format!("Overflow evaluating the history at index {}", k);
+        let k = k as i128;
+        let mut value: i128 = 0;
+        let mut binomial: i128 = 1;
+        for (j, diff) in self.leading_diffs.iter().enumerate() {
+            if j > 0 {
+                // C(k, j) = C(k, j - 1) * (k - j + 1) / j, and the division is always exact
+                binomial = binomial.checked_mul(k - j as i128 + 1).ok_or_else(overflow)? / j as i128;
+            }
+            let term = (*diff as i128).checked_mul(binomial).ok_or_else(overflow)?;
+            value = value.checked_add(term).ok_or_else(overflow)?;
         }
-        last_diff = new_diffs.clone();
-        diffs.push_front(*new_diffs.get(0).unwrap());
+        isize::try_from(value).map_err(|_| overflow())
+    }
+}
+
+#[test]
+fn test_newton_polynomial_fit() {
+    let polynomial = NewtonPolynomial::fit(&[1, 3, 6, 10, 15, 21]).unwrap();
+    assert_eq!(NewtonPolynomial { leading_diffs: vec![1, 2, 1], reached_zeros: true }, polynomial);
+
+    // 1, 2, 4, 8 never gets to a row of zeros, the best we can do is a cubic through all four
+    let polynomial = NewtonPolynomial::fit(&[1, 2, 4, 8]).unwrap();
+    assert_eq!(NewtonPolynomial { leading_diffs: vec![1, 1, 1, 1], reached_zeros: false }, polynomial);
+
+    assert_eq!(NewtonPolynomial { leading_diffs: vec![7], reached_zeros: true }, NewtonPolynomial::fit(&[7]).unwrap());
+    // A blank line isn't a degree 0 history, there's nothing to fit
+    assert_eq!(Err(String::from("Can't fit a polynomial to an empty history")), NewtonPolynomial::fit(&parse_history("  ").unwrap()));
+}
+
+#[test]
+fn test_newton_polynomial_closed_form() {
+    let triangular = NewtonPolynomial::fit(&[1, 3, 6, 10, 15, 21]).unwrap();
+    assert_eq!(2, triangular.degree());
+    assert_eq!(
+        vec![Ratio::from_integer(1), Ratio::new(3, 2), Ratio::new(1, 2)],
+        triangular.coefficients().unwrap()
+    );
+    assert_eq!(Ok(String::from("1/2 n^2 + 3/2 n + 1")), triangular.closed_form());
+
+    let cubic = NewtonPolynomial::fit(&[5, 4, -3, -22, -59]).unwrap();
+    assert_eq!(3, cubic.degree());
+    assert_eq!(Ok(String::from("-n^3 + 5")), cubic.closed_form());
+
+    assert_eq!(Ok(String::from("3 n")), NewtonPolynomial::fit(&[0, 3, 6, 9, 12, 15]).unwrap().closed_form());
+    assert_eq!(Ok(String::from("-7")), NewtonPolynomial::fit(&[-7, -7, -7]).unwrap().closed_form());
+    assert_eq!(Ok(String::from("0")), NewtonPolynomial::fit(&[0, 0, 0]).unwrap().closed_form());
+}
+
+#[test]
+fn test_newton_polynomial_closed_form_overflow() {
+    // 0, 1, 0, 1, ... never reaches a row of zeros, so 40 values need a degree 39
+    // polynomial, and 34! is already too big for an i128
+    let history: Vec<isize> = (0..40).map(|n| n % 2).collect();
+    let polynomial = NewtonPolynomial::fit(&history).unwrap();
+    assert_eq!(
+        Err(String::from("Overflow working out the closed form of a degree 39 polynomial")),
+        polynomial.closed_form()
+    );
+    // It can still be evaluated, the binomials stay small enough
+    assert_eq!(Ok(0), polynomial.value_at(0));
+}
+
+#[test]
+fn test_newton_polynomial_value_at() {
+    let linear = NewtonPolynomial::fit(&[0, 3, 6, 9, 12, 15]).unwrap();
+    assert_eq!(Ok(18), linear.value_at(6));
+    assert_eq!(Ok(-3), linear.value_at(-1));
+    assert_eq!(Ok(3000), linear.value_at(1000));
+
+    let quadratic = NewtonPolynomial::fit(&[10, 13, 16, 21, 30, 45]).unwrap();
+    assert_eq!(Ok(68), quadratic.value_at(6));
+    assert_eq!(Ok(5), quadratic.value_at(-1));
+    // Every index inside the history gives back the history
+    for (idx, val) in [10, 13, 16, 21, 30, 45].into_iter().enumerate() {
+        assert_eq!(Ok(val), quadratic.value_at(idx as isize));
     }
-    diffs.push_back(*history.first().expect("Expected a value in given history!"));
-    let mut val = 0 as isize;
-    for diff in diffs.into_iter() { 
-        val = diff - val;
-    } 
-    val
+
+    let cubes = NewtonPolynomial::fit(&[0, 1, 8, 27, 64]).unwrap();
+    assert_eq!(Ok(1_000_000_000), cubes.value_at(1000));
+    assert_eq!(Ok(-1_000_000_000), cubes.value_at(-1000));
+}
+
+#[test]
+fn test_newton_polynomial_overflow() {
+    let cubes = NewtonPolynomial::fit(&[0, 1, 8, 27, 64]).unwrap();
+    assert_eq!(
+        Err(String::from("Overflow evaluating the history at index 10000000")),
+        cubes.value_at(10_000_000)
+    );
+    assert!(NewtonPolynomial::fit(&[isize::MIN, isize::MAX]).is_err());
 }
 
 #[test]
 fn test_calculate_next_value() {
     let history = vec![0, 3, 6, 9, 12, 15];
     assert_eq!(
-        18,
+        Ok(18),
         calculate_next_value(history)
     )
 }
@@ -116,7 +344,27 @@ fn test_calculate_next_value() {
 fn test_calculate_preceding_value() {
     let history = vec![10, 13, 16, 21, 30, 45];
     assert_eq!(
-        5,
+        Ok(5),
         calculate_preceding_value(history)
     )
+}
+
+#[test]
+fn test_degenerate_histories() {
+    assert_eq!(Err(String::from("Can't extrapolate an empty history")), calculate_next_value(vec![]));
+    assert_eq!(Err(String::from("Can't extrapolate an empty history")), calculate_preceding_value(vec![]));
+
+    assert_eq!(Ok(7), calculate_next_value(vec![7]));
+    assert_eq!(Ok(7), calculate_preceding_value(vec![7]));
+    assert_eq!(Ok(0), calculate_next_value(vec![0]));
+
+    assert_eq!(Ok(4), calculate_next_value(vec![4, 4]));
+    assert_eq!(
+        Err(String::from("The differences of [1, 2] never become all zeros")),
+        calculate_next_value(vec![1, 2])
+    );
+    assert_eq!(
+        Err(String::from("The differences of [1, 2, 4, 8] never become all zeros")),
+        calculate_preceding_value(vec![1, 2, 4, 8])
+    );
 }
\ No newline at end of file
@@ -0,0 +1,207 @@
+use std::fmt;
+use std::ops::{Index, IndexMut};
+
+// A rectangular grid of cells, stored row by row in a single Vec.
+// Positions are (row, col) pairs, like the (i, j) coordinates the grid puzzles use.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+// Offsets to the neighbours of a cell, clockwise from the one above
+pub const ORTHOGONAL: [(isize, isize); 4] = [(-1, 0), (0, 1), (1, 0), (0, -1)];
+pub const ALL_AROUND: [(isize, isize); 8] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+    (1, 0),
+    (1, -1),
+    (0, -1),
+];
+
+impl Grid<char> {
+    // Every line is a row, and every line has to be the same length
+    pub fn from_lines<S: AsRef<str>>(lines: &[S]) -> Result<Self, String> {
+        let mut cells: Vec<char> = vec![];
+        let mut width = 0;
+        for (idx, line) in lines.iter().enumerate() {
+            let row: Vec<char> = line.as_ref().chars().collect();
+            if idx == 0 {
+                width = row.len();
+            } else if row.len() != width {
+                return Err(format!("Line {} is {} long, but the lines above it are {} long", idx + 1, row.len(), width))
+            }
+            cells.extend(row);
+        }
+        Ok(Self { width, height: lines.len(), cells })
+    }
+}
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, cells: Vec<T>) -> Self {
+        assert_eq!(width * height, cells.len(), "Expected a cell for every position in the grid");
+        Self { width, height, cells }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, (row, col): (usize, usize)) -> Option<&T> {
+        if row < self.height && col < self.width {
+            self.cells.get(row * self.width + col)
+        } else {
+            None
+        }
+    }
+
+    // The position at a signed offset from pos, if that's still inside the grid
+    pub fn offset(&self, (row, col): (usize, usize), (d_row, d_col): (isize, isize)) -> Option<(usize, usize)> {
+        let row = row.checked_add_signed(d_row)?;
+        let col = col.checked_add_signed(d_col)?;
+        if row < self.height && col < self.width {
+            Some((row, col))
+        } else {
+            None
+        }
+    }
+
+    // Positions of the (up to) 4 cells above, right, below and left of pos
+    pub fn neighbours4(&self, pos: (usize, usize)) -> impl Iterator<Item = (usize, usize)> + '_ {
+        ORTHOGONAL.iter().filter_map(move |offset| self.offset(pos, *offset))
+    }
+
+    // Positions of the (up to) 8 cells around pos, diagonals included
+    pub fn neighbours8(&self, pos: (usize, usize)) -> impl Iterator<Item = (usize, usize)> + '_ {
+        ALL_AROUND.iter().filter_map(move |offset| self.offset(pos, *offset))
+    }
+
+    // Every position in the grid, row by row
+    pub fn positions(&self) -> impl Iterator<Item = (usize, usize)> {
+        let width = self.width;
+        (0..self.height).flat_map(move |row| (0..width).map(move |col| (row, col)))
+    }
+
+    pub fn row(&self, row: usize) -> &[T] {
+        &self.cells[row * self.width..(row + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        // chunks panics on 0, an empty grid has no rows either way
+        self.cells.chunks(self.width.max(1))
+    }
+
+    pub fn column(&self, col: usize) -> impl Iterator<Item = &T> {
+        (0..self.height).map(move |row| &self.cells[row * self.width + col])
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(move |col| self.column(col))
+    }
+}
+
+impl<T: Clone> Grid<T> {
+    // Rows become columns
+    pub fn transpose(&self) -> Self {
+        let cells = self.columns().flat_map(|column| column.cloned()).collect();
+        Self { width: self.height, height: self.width, cells }
+    }
+
+    pub fn rotate_clockwise(&self) -> Self {
+        let cells = (0..self.width)
+            .flat_map(|col| (0..self.height).rev().map(move |row| self[(row, col)].clone()))
+            .collect();
+        Self { width: self.height, height: self.width, cells }
+    }
+
+    pub fn rotate_counterclockwise(&self) -> Self {
+        let cells = (0..self.width).rev()
+            .flat_map(|col| (0..self.height).map(move |row| self[(row, col)].clone()))
+            .collect();
+        Self { width: self.height, height: self.width, cells }
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, pos: (usize, usize)) -> &T {
+        self.get(pos).unwrap_or_else(|| panic!("{:?} is outside the {}x{} grid", pos, self.width, self.height))
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, (row, col): (usize, usize)) -> &mut T {
+        assert!(row < self.height && col < self.width, "{:?} is outside the {}x{} grid", (row, col), self.width, self.height);
+        &mut self.cells[row * self.width + col]
+    }
+}
+
+// Prints the grid a row per line, the way the puzzle inputs look
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for row in 0..self.height {
+            for cell in self.row(row) {
+                write!(f, "{}", cell)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_grid_from_lines() {
+    let grid = Grid::from_lines(&["abc", "def"]).unwrap();
+    assert_eq!(3, grid.width());
+    assert_eq!(2, grid.height());
+    assert_eq!('a', grid[(0, 0)]);
+    assert_eq!('f', grid[(1, 2)]);
+    assert_eq!(None, grid.get((2, 0)));
+    assert_eq!(None, grid.get((0, 3)));
+    assert_eq!(
+        Err(String::from("Line 2 is 2 long, but the lines above it are 3 long")),
+        Grid::from_lines(&["abc", "de"])
+    );
+}
+
+#[test]
+fn test_grid_neighbours() {
+    let grid = Grid::from_lines(&["abc", "def", "ghi"]).unwrap();
+    let around_corner: Vec<char> = grid.neighbours8((0, 0)).map(|pos| grid[pos]).collect();
+    assert_eq!(vec!['b', 'e', 'd'], around_corner);
+    let around_middle: Vec<char> = grid.neighbours8((1, 1)).map(|pos| grid[pos]).collect();
+    assert_eq!(vec!['a', 'b', 'c', 'f', 'i', 'h', 'g', 'd'], around_middle);
+    let beside_edge: Vec<char> = grid.neighbours4((1, 2)).map(|pos| grid[pos]).collect();
+    assert_eq!(vec!['c', 'i', 'e'], beside_edge);
+    assert_eq!(None, grid.offset((0, 0), (-1, 0)));
+    assert_eq!(Some((2, 1)), grid.offset((0, 2), (2, -1)));
+}
+
+#[test]
+fn test_grid_rows_and_columns() {
+    let grid = Grid::from_lines(&["abc", "def"]).unwrap();
+    let rows: Vec<String> = grid.rows().map(|row| row.iter().collect()).collect();
+    assert_eq!(vec!["abc", "def"], rows);
+    let columns: Vec<String> = grid.columns().map(|column| column.collect()).collect();
+    assert_eq!(vec!["ad", "be", "cf"], columns);
+    assert_eq!(6, grid.positions().count());
+}
+
+#[test]
+fn test_grid_transpose_and_rotate() {
+    let grid = Grid::from_lines(&["abc", "def"]).unwrap();
+    assert_eq!("ad\nbe\ncf\n", grid.transpose().to_string());
+    assert_eq!("da\neb\nfc\n", grid.rotate_clockwise().to_string());
+    assert_eq!("cf\nbe\nad\n", grid.rotate_counterclockwise().to_string());
+    assert_eq!(grid, grid.rotate_clockwise().rotate_counterclockwise());
+    assert_eq!(grid.rotate_clockwise().rotate_clockwise(), grid.rotate_counterclockwise().rotate_counterclockwise());
+}
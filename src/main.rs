@@ -9,17 +9,18 @@ mod day6;
 mod day7;
 mod day8;
 mod day9;
+mod grid;
 
 use clap::Parser;
 use crate::day1::{run_day_1_part_1, run_day_1_part_2};
 use crate::day2::{run_day_2_part_1, run_day_2_part_2};
-use crate::day3::{run_day_3_part_1, run_day_3_part_2};
-use crate::day4::{run_day_4_part_1, run_day_4_part_2};
+use crate::day3::{run_day_3_part_1, run_day_3_part_1_streaming, run_day_3_part_2, run_day_3_part_2_streaming, run_day_3_render, run_day_3_symbol_report};
+use crate::day4::{run_day_4_cascade, run_day_4_generate, run_day_4_part_1, run_day_4_part_2, run_day_4_stats};
 use crate::day5::run_day_5_part_1;
 use crate::day6::{run_day_6_part_1, run_day_6_part_2};
 use crate::day7::{run_day_7_part_1, run_day_7_part_2};
-use crate::day8::{run_day_8_part_1, run_day_8_part_2};
-use crate::day9::{run_day_9_part_1, run_day_9_part_2};
+use crate::day8::{run_day_8_dot, run_day_8_lcm_check, run_day_8_part_1, run_day_8_part_2};
+use crate::day9::{run_day_9_closed_forms, run_day_9_part_1, run_day_9_part_2};
 
 use std::error::Error;
 #[derive(Parser, Debug)]
@@ -32,6 +33,95 @@ struct Args {
     /// Part 1 or 2 Problem of the Day, value must be either 1 or 2
     #[arg(short, long)]
     part: u8,
+
+    /// Day 1 part 2 only: the digit words to look for, either english, german, french,
+    /// spanish, dutch or the path of a file with a word and its digit on each line
+    #[arg(long, default_value = "english")]
+    vocabulary: String,
+
+    /// Day 1 only: list the digits found on every line, and fail on lines without any
+    #[arg(long)]
+    strict: bool,
+
+    /// Day 2 part 1 only: the cubes in the bag, either a list like red=12,green=13,blue=14
+    /// or the path of a file containing one
+    #[arg(long, default_value = "red=12,green=13,blue=14")]
+    bag: String,
+
+    /// Day 2 part 1 only: explain why each excluded game isn't possible
+    #[arg(long)]
+    explain: bool,
+
+    /// Day 3 only: report the part numbers around each kind of symbol instead of solving
+    #[arg(long)]
+    symbol_report: bool,
+
+    /// Day 3 part 2 only: which symbol is a gear, how many numbers it needs around it
+    /// and how to combine them (product, sum, min or max)
+    #[arg(long, default_value = "*:2:product")]
+    gear_rule: String,
+
+    /// Day 3 only: read the schematic a row at a time instead of all at once, for huge inputs
+    #[arg(long)]
+    stream: bool,
+
+    /// The puzzle input to read instead of inputs/dayN.txt, for Day 3 with --stream
+    /// and Day 4
+    #[arg(long)]
+    input: Option<String>,
+
+    /// Day 3 only: print the schematic with part numbers and gears highlighted instead of solving
+    #[arg(long)]
+    render: bool,
+
+    /// With --render, mark part numbers with [] and gears with {} instead of using colours
+    #[arg(long)]
+    no_color: bool,
+
+    /// Day 4 only: explain how each card's copies add up, as a table, a tree of the copies
+    /// or a Graphviz DOT graph, instead of solving
+    #[arg(long, num_args = 0..=1, default_missing_value = "table")]
+    cascade: Option<String>,
+
+    /// Day 4 only: print this many random cards instead of solving
+    #[arg(long)]
+    generate: Option<usize>,
+
+    /// With --generate, how many winning numbers each card has
+    #[arg(long, default_value_t = 10)]
+    winning: usize,
+
+    /// With --generate, how many scratched numbers each card has
+    #[arg(long, default_value_t = 25)]
+    scratched: usize,
+
+    /// With --generate, the range the numbers are picked from
+    #[arg(long, default_value = "1-99")]
+    numbers: String,
+
+    /// With --generate, the seed for the random numbers
+    #[arg(long, default_value_t = 2023)]
+    seed: u64,
+
+    /// Day 4 only: print how many matches the cards have and what they're worth instead of solving
+    #[arg(long)]
+    stats: bool,
+
+    /// Day 8 part 2 only: check whether the LCM shortcut holds for each **A start
+    #[arg(long)]
+    check_lcm: bool,
+
+    /// Day 8 only: print the node network as a Graphviz DOT graph instead of solving
+    #[arg(long)]
+    dot: bool,
+
+    /// With --dot, colour nodes by connected component
+    #[arg(long)]
+    components: bool,
+
+    /// Day 9 only: print the degree and closed form of each history instead of solving
+    #[arg(long)]
+    closed_form: bool,
 }
 
 
@@ -48,30 +138,67 @@ fn main() -> Result<(), Box<dyn Error>> {
         _ => return Err("--part must be 1 or 2".into())
     }
 
+    if day == 8 && args.dot {
+        run_day_8_dot(args.components)?;
+        return Ok(())
+    }
+    if day == 3 && args.symbol_report {
+        run_day_3_symbol_report()?;
+        return Ok(())
+    }
+    if day == 3 && args.render {
+        run_day_3_render(&args.gear_rule, !args.no_color)?;
+        return Ok(())
+    }
+    if let (4, Some(format)) = (day, &args.cascade) {
+        run_day_4_cascade(format, args.input.as_deref().unwrap_or("inputs/day4.txt"))?;
+        return Ok(())
+    }
+    if let (4, Some(count)) = (day, args.generate) {
+        run_day_4_generate(count, args.winning, args.scratched, &args.numbers, args.seed)?;
+        return Ok(())
+    }
+    if day == 4 && args.stats {
+        run_day_4_stats(args.input.as_deref().unwrap_or("inputs/day4.txt"))?;
+        return Ok(())
+    }
+    if day == 9 && args.closed_form {
+        run_day_9_closed_forms()?;
+        return Ok(())
+    }
+
     match (day, part) {
         (1, 1) => {
-            run_day_1_part_1();
+            run_day_1_part_1(args.strict)?;
         },
         (1, 2) => {
-            run_day_1_part_2();
+            run_day_1_part_2(&args.vocabulary, args.strict)?;
         },
         (2, 1) => {
-            run_day_2_part_1();
+            run_day_2_part_1(&args.bag, args.explain)?;
         },
         (2, 2) => {
-            run_day_2_part_2();
+            run_day_2_part_2()?;
         },
         (3, 1) => {
-            run_day_3_part_1();
+            if args.stream {
+                run_day_3_part_1_streaming(args.input.as_deref().unwrap_or("inputs/day3.txt"))?;
+            } else {
+                run_day_3_part_1()?;
+            }
         },
         (3, 2) => {
-            run_day_3_part_2();
+            if args.stream {
+                run_day_3_part_2_streaming(args.input.as_deref().unwrap_or("inputs/day3.txt"), &args.gear_rule)?;
+            } else {
+                run_day_3_part_2(&args.gear_rule)?;
+            }
         }
         (4, 1) => {
-            run_day_4_part_1();
+            run_day_4_part_1(args.input.as_deref().unwrap_or("inputs/day4.txt"))?;
         },
         (4, 2) => {
-            run_day_4_part_2();
+            run_day_4_part_2(args.input.as_deref().unwrap_or("inputs/day4.txt"))?;
         },
         (5, 1) => {
             run_day_5_part_1();
@@ -89,16 +216,20 @@ fn main() -> Result<(), Box<dyn Error>> {
             run_day_7_part_2();
         },
         (8, 1) => {
-            run_day_8_part_1();
+            run_day_8_part_1()?;
         },
         (8, 2) => {
-            run_day_8_part_2();
+            if args.check_lcm {
+                run_day_8_lcm_check()?;
+            } else {
+                run_day_8_part_2()?;
+            }
         },
         (9, 1) => {
-            run_day_9_part_1();
+            run_day_9_part_1()?;
         },
         (9, 2) => {
-            run_day_9_part_2();
+            run_day_9_part_2()?;
         }
         _ => {
             println!("Haven't done that one yet 🎅☃️🎄")